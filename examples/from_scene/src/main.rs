@@ -1,7 +1,6 @@
 use raytracing_lib::*;
 
 use nalgebra_glm::Vec3;
-use std::fs;
 use std::{error::Error, path::PathBuf};
 
 fn main() -> Result<(), Box<dyn Error>> {
@@ -17,11 +16,13 @@ fn main() -> Result<(), Box<dyn Error>> {
             focus_distance: 10.0,
         })
         .set_vertical_fov(20.0)
+        .set_shutter(0.0, 1.0)
         .build();
 
-    let scene: raytracing_lib::scene::serialization::Scene =
-        serde_yaml::from_str(&fs::read_to_string("examples/from_scene/scene.yaml")?).unwrap();
+    let scene =
+        raytracing_lib::scene::serialization::Scene::from_file(&"examples/from_scene/scene.yaml")?;
 
+    let background = scene.background()?;
     let (_material_atlas, world) =
         std::convert::TryInto::<(MaterialAtlas, World)>::try_into(scene)?;
 
@@ -33,6 +34,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     // Render
     let p = PathBuf::from("laifilfse.ppm");
     Renderer::new(world, camera)
+        .background(background)
         .width(image_width)
         .height(image_height)
         .bounces(50)
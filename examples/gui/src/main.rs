@@ -4,7 +4,6 @@ mod render_progress;
 mod ui_message;
 
 use std::error::Error;
-use std::fs;
 use std::time::Instant;
 
 use iced::advanced::graphics::image::image_rs::EncodableLayout;
@@ -129,13 +128,15 @@ impl Daemon {
                         focus_distance: 10.0,
                     })
                     .set_vertical_fov(20.0)
+                    .set_shutter(0.0, 1.0)
                     .build();
 
-                let scene: raytracing_lib::scene::serialization::Scene = serde_yaml::from_str(
-                    &fs::read_to_string("examples/from_scene/scene.yaml").unwrap(),
+                let scene = raytracing_lib::scene::serialization::Scene::from_file(
+                    &"examples/from_scene/scene.yaml",
                 )
                 .unwrap();
 
+                let background = scene.background().unwrap();
                 let (material_atlas, world) =
                     std::convert::TryInto::<(MaterialAtlas, World)>::try_into(scene).unwrap();
 
@@ -144,6 +145,7 @@ impl Daemon {
                         camera,
                         world,
                         material_atlas,
+                        background,
                         image_width: self.render_controls.img_width as usize,
                         image_height: self.render_controls.img_height as usize,
                         bounces: self.render_controls.bounces,
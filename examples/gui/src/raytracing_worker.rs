@@ -37,7 +37,8 @@ pub fn raytracer_worker() -> impl Stream<Item = Event> {
                             .width(rr.image_width)
                             .height(rr.image_height)
                             .bounces(rr.bounces)
-                            .samples(rr.samples);
+                            .samples(rr.samples)
+                            .background(Arc::clone(&rr.background));
                     let render_pass_rx = renderer.get_render_pass_rx();
 
                     // spawn the renderer in another thread (not cancellable)
@@ -96,6 +97,7 @@ pub struct RenderRequest {
     pub camera: Arc<raytracing_lib::Camera>,
     pub world: raytracing_lib::World,
     pub material_atlas: raytracing_lib::MaterialAtlas,
+    pub background: Arc<dyn raytracing_lib::background::Background>,
     pub image_width: usize,
     pub image_height: usize,
     pub bounces: usize,
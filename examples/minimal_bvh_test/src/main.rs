@@ -33,7 +33,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     let world = world_builder.build();
 
     let ray = Ray::new(Vec3::new(-10.0, 1.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
-    let bvh = world.get_hittables();
+    let bvh = world.get_hittables().unwrap();
     dbg!(bvh.hit(&ray, 0.0, f32::INFINITY));
     dbg!(bvh.left.bounding_box(0.0, f32::INFINITY));
     dbg!(bvh.right.bounding_box(0.0, f32::INFINITY));
@@ -37,7 +37,7 @@ pub fn criterion_benchmark(c: &mut Criterion) {
     let world = world_builder.build();
 
     let ray = Ray::new(Vec3::new(-10.0, 1.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
-    let bvh = world.get_hittables();
+    let bvh = world.get_hittables().unwrap();
 
     let mut group = c.benchmark_group("group");
     group.sample_size(100).warm_up_time(Duration::from_secs(5));
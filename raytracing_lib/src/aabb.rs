@@ -2,6 +2,9 @@ use nalgebra_glm::Vec3;
 
 use crate::Ray;
 
+// minimal thickness given to the bounding box of flat objects like axis aligned triangles
+pub const AABB_PADDING: f32 = 1e-4;
+
 #[allow(clippy::upper_case_acronyms)]
 #[derive(Clone, Copy, Debug)]
 pub struct AABB {
@@ -28,6 +31,37 @@ impl AABB {
         true
     }
 
+    // part of [t_min, t_max] during which the ray is inside the box
+    pub fn intersect(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<(f32, f32)> {
+        let (mut t_min, mut t_max) = (t_min, t_max);
+        for a in 0..3 {
+            let inv_d: f32 = 1f32 / r.direction[a];
+            let mut t0: f32 = (self.min[a] - r.origin[a]) * inv_d;
+            let mut t1: f32 = (self.max[a] - r.origin[a]) * inv_d;
+            if inv_d < 0f32 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            t_min = f32::max(t0, t_min);
+            t_max = f32::min(t1, t_max);
+            if t_max <= t_min {
+                return None;
+            }
+        }
+        Some((t_min, t_max))
+    }
+
+    // grows the axes along which the box is thinner than min_size, flat boxes are never hit
+    pub fn padded(&self, min_size: f32) -> Self {
+        let mut padded = *self;
+        for a in 0..3 {
+            if padded.max[a] - padded.min[a] < min_size {
+                padded.min[a] -= min_size / 2.0;
+                padded.max[a] += min_size / 2.0;
+            }
+        }
+        padded
+    }
+
     pub fn surrounding_box(box0: &Self, box1: &Self) -> Self {
         let small = Vec3::new(
             f32::min(box0.min[0], box1.min[0]),
@@ -0,0 +1,225 @@
+use super::Background;
+use crate::ray::Ray;
+use nalgebra_glm::{normalize, Vec3};
+use std::f32::consts::PI;
+use std::path::Path;
+
+// Image based lighting from an equirectangular (latitude/longitude) map, the top row of the
+// image is the +y direction. Directions are importance sampled proportionally to the luminance
+// of the map so that bright features like the sun are found by explicit light sampling.
+pub struct EnvironmentMap {
+    width: usize,
+    height: usize,
+    data: Vec<Vec3>,
+    rotation: f32,
+    intensity: f32,
+    distribution: Distribution2D,
+}
+
+impl EnvironmentMap {
+    pub fn new(width: usize, height: usize, data: Vec<Vec3>) -> Result<Self, String> {
+        if width == 0 || height == 0 || data.len() != width * height {
+            return Err(format!(
+                "Environment map of {}x{} pixels needs {} values, got {}",
+                width,
+                height,
+                width * height,
+                data.len()
+            ));
+        }
+
+        // weight each pixel by its luminance and by the solid angle it covers on the sphere
+        let weights: Vec<f32> = data
+            .iter()
+            .enumerate()
+            .map(|(i, color)| {
+                let theta = PI * ((i / width) as f32 + 0.5) / height as f32;
+                luminance(color) * f32::sin(theta)
+            })
+            .collect();
+        let distribution = Distribution2D::new(&weights, width, height);
+
+        Ok(EnvironmentMap {
+            width,
+            height,
+            data,
+            rotation: 0.0,
+            intensity: 1.0,
+            distribution,
+        })
+    }
+
+    // loads a Radiance RGBE (.hdr) file
+    pub fn from_file<P: AsRef<Path>>(path: &P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let image = image::open(path)
+            .map_err(|err| format!("Cannot load environment map {}: {}", path.display(), err))?
+            .into_rgb32f();
+        let (width, height) = (image.width() as usize, image.height() as usize);
+        let data = image
+            .pixels()
+            .map(|p| Vec3::new(p[0], p[1], p[2]))
+            .collect();
+        EnvironmentMap::new(width, height, data)
+    }
+
+    // rotation of the map around the y axis, in degrees
+    pub fn rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation * PI / 180.0;
+        self
+    }
+
+    pub fn intensity(mut self, intensity: f32) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    fn direction_to_uv(&self, direction: &Vec3) -> (f32, f32) {
+        let direction = normalize(direction);
+        let theta = f32::acos(direction.y.clamp(-1.0, 1.0));
+        let phi = f32::atan2(direction.z, direction.x) - self.rotation;
+        let u = (phi / (2.0 * PI)).rem_euclid(1.0);
+        (u, theta / PI)
+    }
+
+    fn uv_to_direction(&self, u: f32, v: f32) -> Vec3 {
+        let theta = v * PI;
+        let phi = u * 2.0 * PI + self.rotation;
+        Vec3::new(
+            f32::sin(theta) * f32::cos(phi),
+            f32::cos(theta),
+            f32::sin(theta) * f32::sin(phi),
+        )
+    }
+
+    fn lookup(&self, u: f32, v: f32) -> Vec3 {
+        let x = ((u * self.width as f32) as usize).min(self.width - 1);
+        let y = ((v * self.height as f32) as usize).min(self.height - 1);
+        self.intensity * self.data[y * self.width + x]
+    }
+}
+
+impl Background for EnvironmentMap {
+    fn color(&self, ray: &Ray) -> Vec3 {
+        let (u, v) = self.direction_to_uv(&ray.direction);
+        self.lookup(u, v)
+    }
+
+    fn sample(&self) -> Option<(Vec3, f32)> {
+        let (u, v, pdf_uv) = self
+            .distribution
+            .sample(rand::random::<f32>(), rand::random::<f32>())?;
+        let sin_theta = f32::sin(v * PI);
+        if sin_theta <= 0.0 {
+            return None;
+        }
+        // change of variable from the unit square to solid angle
+        let pdf = pdf_uv / (2.0 * PI * PI * sin_theta);
+        Some((self.uv_to_direction(u, v), pdf))
+    }
+
+    fn pdf(&self, direction: &Vec3) -> f32 {
+        let (u, v) = self.direction_to_uv(direction);
+        let sin_theta = f32::sin(v * PI);
+        if sin_theta <= 0.0 {
+            return 0.0;
+        }
+        self.distribution.pdf(u, v) / (2.0 * PI * PI * sin_theta)
+    }
+}
+
+fn luminance(color: &Vec3) -> f32 {
+    0.2126 * color.x + 0.7152 * color.y + 0.0722 * color.z
+}
+
+// piecewise constant distribution over [0, 1)
+struct Distribution1D {
+    func: Vec<f32>,
+    cdf: Vec<f32>,
+    integral: f32,
+}
+
+impl Distribution1D {
+    fn new(func: &[f32]) -> Self {
+        let n = func.len();
+        let mut cdf = Vec::with_capacity(n + 1);
+        cdf.push(0.0);
+        for (i, f) in func.iter().enumerate() {
+            cdf.push(cdf[i] + f / n as f32);
+        }
+        let integral = cdf[n];
+        if integral > 0.0 {
+            cdf.iter_mut().for_each(|c| *c /= integral);
+        } else {
+            // a black row or image, fall back to uniform sampling
+            cdf.iter_mut()
+                .enumerate()
+                .for_each(|(i, c)| *c = i as f32 / n as f32);
+        }
+        Distribution1D {
+            func: func.to_vec(),
+            cdf,
+            integral,
+        }
+    }
+
+    // returns the sampled value in [0, 1), its pdf and the index of the sampled segment
+    fn sample(&self, random: f32) -> (f32, f32, usize) {
+        let n = self.func.len();
+        let index = (self.cdf.partition_point(|&c| c <= random) - 1).min(n - 1);
+        let segment = self.cdf[index + 1] - self.cdf[index];
+        let offset = if segment > 0.0 {
+            (random - self.cdf[index]) / segment
+        } else {
+            0.0
+        };
+        let pdf = if self.integral > 0.0 {
+            self.func[index] / self.integral
+        } else {
+            1.0
+        };
+        ((index as f32 + offset) / n as f32, pdf, index)
+    }
+}
+
+// piecewise constant distribution over [0, 1)², built from a row major grid of weights
+struct Distribution2D {
+    conditionals: Vec<Distribution1D>,
+    marginal: Distribution1D,
+}
+
+impl Distribution2D {
+    fn new(weights: &[f32], width: usize, height: usize) -> Self {
+        let conditionals: Vec<Distribution1D> = weights
+            .chunks_exact(width)
+            .take(height)
+            .map(Distribution1D::new)
+            .collect();
+        let marginal_func: Vec<f32> = conditionals.iter().map(|c| c.integral).collect();
+        Distribution2D {
+            conditionals,
+            marginal: Distribution1D::new(&marginal_func),
+        }
+    }
+
+    // returns (u, v, pdf), None if the distribution has no energy at all
+    fn sample(&self, random_u: f32, random_v: f32) -> Option<(f32, f32, f32)> {
+        if self.marginal.integral <= 0.0 {
+            return None;
+        }
+        let (v, pdf_v, row) = self.marginal.sample(random_v);
+        let (u, pdf_u, _) = self.conditionals[row].sample(random_u);
+        Some((u, v, pdf_u * pdf_v))
+    }
+
+    fn pdf(&self, u: f32, v: f32) -> f32 {
+        if self.marginal.integral <= 0.0 {
+            return 0.0;
+        }
+        let height = self.conditionals.len();
+        let width = self.conditionals[0].func.len();
+        let x = ((u * width as f32) as usize).min(width - 1);
+        let y = ((v * height as f32) as usize).min(height - 1);
+        self.conditionals[y].func[x] / self.marginal.integral
+    }
+}
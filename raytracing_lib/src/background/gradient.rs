@@ -0,0 +1,39 @@
+use super::Background;
+use crate::ray::Ray;
+use nalgebra_glm::{dot, lerp, normalize, Vec3};
+
+// blends linearly between two colors depending on how much the ray goes along the up axis
+pub struct Gradient {
+    pub bottom: Vec3,
+    pub top: Vec3,
+    up: Vec3,
+}
+
+impl Gradient {
+    pub fn new(bottom: Vec3, top: Vec3, up: Vec3) -> Self {
+        Gradient {
+            bottom,
+            top,
+            up: normalize(&up),
+        }
+    }
+}
+
+impl Background for Gradient {
+    fn color(&self, ray: &Ray) -> Vec3 {
+        let unit_direction = normalize(&ray.direction);
+        let t = 0.5 * (dot(&unit_direction, &self.up) + 1.0); // t is between 0.0 and 1.0
+        lerp(&self.bottom, &self.top, t)
+    }
+}
+
+impl Default for Gradient {
+    // white to light blue sky
+    fn default() -> Self {
+        Gradient::new(
+            Vec3::new(1.0, 1.0, 1.0),
+            Vec3::new(0.5, 0.7, 1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        )
+    }
+}
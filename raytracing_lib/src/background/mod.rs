@@ -0,0 +1,30 @@
+use crate::ray::Ray;
+use nalgebra_glm::Vec3;
+
+mod environment_map;
+mod gradient;
+mod no_background;
+mod sky;
+mod solid_color;
+
+pub use environment_map::EnvironmentMap;
+pub use gradient::Gradient;
+pub use no_background::NoBackground;
+pub use sky::PreethamSky;
+pub use solid_color::SolidColor;
+
+pub trait Background: Send + Sync {
+    // returns the light coming from the background for a ray that did not hit anything
+    fn color(&self, ray: &Ray) -> Vec3;
+    // samples a direction toward the background proportionally to the light it sends, returns
+    // the unit direction and its probability density over the sphere of directions, or None if
+    // the background cannot be sampled and has to be found by random bounces
+    fn sample(&self) -> Option<(Vec3, f32)> {
+        None
+    }
+    // returns the probability density of sample returning the given direction
+    #[allow(unused_variables)]
+    fn pdf(&self, direction: &Vec3) -> f32 {
+        0.0
+    }
+}
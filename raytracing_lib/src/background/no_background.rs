@@ -0,0 +1,14 @@
+use super::Background;
+use crate::ray::Ray;
+use nalgebra_glm::Vec3;
+
+// a black background, the only light in the scene comes from emissive materials
+#[derive(Default)]
+pub struct NoBackground;
+
+impl Background for NoBackground {
+    #[allow(unused_variables)]
+    fn color(&self, ray: &Ray) -> Vec3 {
+        Vec3::new(0.0, 0.0, 0.0)
+    }
+}
@@ -0,0 +1,157 @@
+use super::Background;
+use crate::light::SunLight;
+use crate::ray::Ray;
+use nalgebra_glm::{dot, normalize, Vec3};
+use std::f32::consts::PI;
+
+// luminances of the model are in kcd/m², this brings a clear day sky around 1
+const LUMINANCE_SCALE: f32 = 0.1;
+// luminance of the sun before it goes through the atmosphere, in kcd/m²
+const EXTRATERRESTRIAL_SUN_LUMINANCE: f32 = 2.0e6;
+// half of the angle covered by the sun disk, in degrees
+const SUN_ANGULAR_RADIUS: f32 = 0.2667;
+
+// Analytic daylight sky from Preetham, Shirley and Smits, "A Practical Analytic Model for
+// Daylight" (SIGGRAPH 1999). The turbidity goes from 2 (very clear sky) to 10 (hazy sky).
+// The sun itself is not part of the background, use sun_light to get the matching light.
+pub struct PreethamSky {
+    sun_direction: Vec3,
+    turbidity: f32,
+    intensity: f32,
+    zenith: Vec3,
+    perez_y: [f32; 5],
+    perez_x: [f32; 5],
+    perez_yy: [f32; 5],
+}
+
+impl PreethamSky {
+    // sun_direction points from the scene toward the sun
+    pub fn new(sun_direction: Vec3, turbidity: f32) -> Self {
+        let sun_direction = normalize(&sun_direction);
+        let t = turbidity.clamp(1.7, 10.0);
+        // the model is only valid for a sun above the horizon
+        let theta_s = f32::acos(sun_direction.y.clamp(0.0, 1.0));
+
+        let chi = (4.0 / 9.0 - t / 120.0) * (PI - 2.0 * theta_s);
+        let zenith_luminance = (4.0453 * t - 4.9710) * f32::tan(chi) - 0.2155 * t + 2.4192;
+        let (t2, s, s2, s3) = (t * t, theta_s, theta_s * theta_s, theta_s.powi(3));
+        let zenith_x = (0.00166 * s3 - 0.00375 * s2 + 0.00209 * s) * t2
+            + (-0.02903 * s3 + 0.06377 * s2 - 0.03202 * s + 0.00394) * t
+            + (0.11693 * s3 - 0.21196 * s2 + 0.06052 * s + 0.25886);
+        let zenith_y = (0.00275 * s3 - 0.00610 * s2 + 0.00317 * s) * t2
+            + (-0.04214 * s3 + 0.08970 * s2 - 0.04153 * s + 0.00516) * t
+            + (0.15346 * s3 - 0.26756 * s2 + 0.06670 * s + 0.26688);
+
+        let perez_y = [
+            0.1787 * t - 1.4630,
+            -0.3554 * t + 0.4275,
+            -0.0227 * t + 5.3251,
+            0.1206 * t - 2.5771,
+            -0.0670 * t + 0.3703,
+        ];
+        let perez_x = [
+            -0.0193 * t - 0.2592,
+            -0.0665 * t + 0.0008,
+            -0.0004 * t + 0.2125,
+            -0.0641 * t - 0.8989,
+            -0.0033 * t + 0.0452,
+        ];
+        let perez_yy = [
+            -0.0167 * t - 0.2608,
+            -0.0950 * t + 0.0092,
+            -0.0079 * t + 0.2102,
+            -0.0441 * t - 1.6537,
+            -0.0109 * t + 0.0529,
+        ];
+
+        // the perez functions are normalized by their value at the zenith
+        let zenith = Vec3::new(
+            zenith_luminance / perez(&perez_y, 0.0, theta_s),
+            zenith_x / perez(&perez_x, 0.0, theta_s),
+            zenith_y / perez(&perez_yy, 0.0, theta_s),
+        );
+
+        PreethamSky {
+            sun_direction,
+            turbidity: t,
+            intensity: 1.0,
+            zenith,
+            perez_y,
+            perez_x,
+            perez_yy,
+        }
+    }
+
+    pub fn intensity(mut self, intensity: f32) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    // the sun disk as seen through this sky, reddened by the atmosphere when close to the horizon
+    pub fn sun_light(&self) -> SunLight {
+        let cos_theta_s = self.sun_direction.y;
+        if cos_theta_s <= 0.0 {
+            return SunLight::new(
+                self.sun_direction,
+                Vec3::new(0.0, 0.0, 0.0),
+                SUN_ANGULAR_RADIUS,
+            );
+        }
+        // relative optical mass and transmittance of the atmosphere (appendix of the paper,
+        // ozone and water vapour absorption left out), evaluated for red, green and blue
+        let theta_s_degrees = f32::acos(cos_theta_s).to_degrees();
+        let optical_mass = 1.0 / (cos_theta_s + 0.15 * f32::powf(93.885 - theta_s_degrees, -1.253));
+        let beta = 0.04608 * self.turbidity - 0.04586;
+        let transmittance = |wavelength: f32| {
+            let rayleigh = f32::exp(-0.008735 * wavelength.powf(-4.08) * optical_mass);
+            let aerosol = f32::exp(-beta * wavelength.powf(-1.3) * optical_mass);
+            rayleigh * aerosol
+        };
+        // wavelengths in micrometers
+        let color = Vec3::new(
+            transmittance(0.680),
+            transmittance(0.550),
+            transmittance(0.440),
+        );
+        SunLight::new(
+            self.sun_direction,
+            color * EXTRATERRESTRIAL_SUN_LUMINANCE * LUMINANCE_SCALE * self.intensity,
+            SUN_ANGULAR_RADIUS,
+        )
+    }
+}
+
+// Perez et al. sky luminance distribution, theta is the angle to the zenith and gamma the angle
+// to the sun
+fn perez(coefficients: &[f32; 5], theta: f32, gamma: f32) -> f32 {
+    let [a, b, c, d, e] = *coefficients;
+    let cos_gamma = f32::cos(gamma);
+    (1.0 + a * f32::exp(b / f32::cos(theta)))
+        * (1.0 + c * f32::exp(d * gamma) + e * cos_gamma * cos_gamma)
+}
+
+impl Background for PreethamSky {
+    fn color(&self, ray: &Ray) -> Vec3 {
+        let mut direction = normalize(&ray.direction);
+        // below the horizon, repeat the sky at the horizon
+        direction.y = f32::max(direction.y, 0.001);
+        let direction = normalize(&direction);
+
+        let theta = f32::acos(direction.y);
+        let gamma = f32::acos(dot(&direction, &self.sun_direction).clamp(-1.0, 1.0));
+        let luminance = self.zenith.x * perez(&self.perez_y, theta, gamma);
+        let x = self.zenith.y * perez(&self.perez_x, theta, gamma);
+        let y = self.zenith.z * perez(&self.perez_yy, theta, gamma);
+
+        // xyY to XYZ to linear sRGB
+        let luminance = luminance * LUMINANCE_SCALE * self.intensity;
+        let cie_x = x / y * luminance;
+        let cie_z = (1.0 - x - y) / y * luminance;
+        let rgb = Vec3::new(
+            3.2406 * cie_x - 1.5372 * luminance - 0.4986 * cie_z,
+            -0.9689 * cie_x + 1.8758 * luminance + 0.0415 * cie_z,
+            0.0557 * cie_x - 0.2040 * luminance + 1.0570 * cie_z,
+        );
+        rgb.map(|c| f32::max(c, 0.0))
+    }
+}
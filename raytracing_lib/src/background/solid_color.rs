@@ -0,0 +1,20 @@
+use super::Background;
+use crate::ray::Ray;
+use nalgebra_glm::Vec3;
+
+pub struct SolidColor {
+    pub color: Vec3,
+}
+
+impl SolidColor {
+    pub fn new(color: Vec3) -> Self {
+        SolidColor { color }
+    }
+}
+
+impl Background for SolidColor {
+    #[allow(unused_variables)]
+    fn color(&self, ray: &Ray) -> Vec3 {
+        self.color
+    }
+}
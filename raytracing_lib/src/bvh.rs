@@ -20,7 +20,13 @@ impl std::fmt::Debug for BVHNode {
 }
 
 impl BVHNode {
-    pub fn new(src_hittables: &[Arc<dyn Hittable>]) -> Result<Self, String> {
+    // moving objects are bounded over the whole time interval [time0, time1] during which they
+    // can be hit
+    pub fn new(
+        src_hittables: &[Arc<dyn Hittable>],
+        time0: f32,
+        time1: f32,
+    ) -> Result<Self, String> {
         let random_axis = AxisIndexes::random_axis();
 
         let left_node;
@@ -34,7 +40,13 @@ impl BVHNode {
                 left_node = Arc::clone(&src_hittables[0]);
                 right_node = Arc::clone(&src_hittables[0]);
             }
-            2 => match box_compare(&src_hittables[0], &src_hittables[1], random_axis)? {
+            2 => match box_compare(
+                &src_hittables[0],
+                &src_hittables[1],
+                random_axis,
+                time0,
+                time1,
+            )? {
                 Ordering::Less | Ordering::Equal => {
                     left_node = Arc::clone(&src_hittables[0]);
                     right_node = Arc::clone(&src_hittables[1]);
@@ -48,16 +60,17 @@ impl BVHNode {
                 // need to perform a clone of the slice to sort it :/
                 let mut sorted_vec = Vec::<Arc<dyn Hittable>>::new();
                 sorted_vec.extend_from_slice(src_hittables);
-                sorted_vec.sort_unstable_by(|a, b| box_compare(a, b, random_axis).unwrap());
+                sorted_vec
+                    .sort_unstable_by(|a, b| box_compare(a, b, random_axis, time0, time1).unwrap());
                 let mid = sorted_vec.len() / 2;
                 let (left_src, right_src) = sorted_vec.split_at(mid);
-                left_node = Arc::new(BVHNode::new(left_src)?);
-                right_node = Arc::new(BVHNode::new(right_src)?);
+                left_node = Arc::new(BVHNode::new(left_src, time0, time1)?);
+                right_node = Arc::new(BVHNode::new(right_src, time0, time1)?);
             }
         };
 
-        let a_box = left_node.bounding_box(0f32, 0f32);
-        let b_box = right_node.bounding_box(0f32, 0f32);
+        let a_box = left_node.bounding_box(time0, time1);
+        let b_box = right_node.bounding_box(time0, time1);
 
         if a_box.is_none() || b_box.is_none() {
             return Err("No bounding box in BVH constructor".into());
@@ -150,9 +163,11 @@ fn box_compare<T: AxisIndex>(
     a: &Arc<dyn Hittable>,
     b: &Arc<dyn Hittable>,
     axis: T,
+    time0: f32,
+    time1: f32,
 ) -> Result<std::cmp::Ordering, String> {
-    let a_box = a.bounding_box(0f32, 0f32);
-    let b_box = b.bounding_box(0f32, 0f32);
+    let a_box = a.bounding_box(time0, time1);
+    let b_box = b.bounding_box(time0, time1);
 
     if a_box.is_none() || b_box.is_none() {
         return Err("No bounding box in BVH constructor".into());
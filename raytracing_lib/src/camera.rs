@@ -1,4 +1,8 @@
-use crate::{object::Position, ray::Ray, utils::random_in_unit_disk};
+use crate::{
+    object::Position,
+    ray::Ray,
+    utils::{rand_range_f32, random_in_unit_disk},
+};
 use nalgebra_glm::{cross, normalize, Vec3};
 use std::sync::Arc;
 
@@ -15,6 +19,7 @@ pub struct CameraBuilder {
     vertical_fov: f32,
     aspect_ratio: f32,
     focus_data: Option<FocusData>,
+    shutter: (f32, f32),
 }
 
 impl CameraBuilder {
@@ -48,6 +53,12 @@ impl CameraBuilder {
         self
     }
 
+    // interval during which the shutter is open, rays are cast at random times inside it
+    pub fn set_shutter(mut self, open: f32, close: f32) -> Self {
+        self.shutter = (open, close);
+        self
+    }
+
     pub fn build(self) -> Arc<Camera> {
         Arc::new(Camera::new(
             self.origin,
@@ -56,6 +67,7 @@ impl CameraBuilder {
             Some(self.look_at),
             Some(self.v_up),
             self.focus_data,
+            self.shutter,
         ))
     }
 }
@@ -69,6 +81,7 @@ pub struct Camera {
     u: Vec3,
     v: Vec3,
     lens_radius: Option<f32>,
+    shutter: (f32, f32),
 }
 
 impl Camera {
@@ -83,6 +96,7 @@ impl Camera {
         look_at: Option<Vec3>,
         v_up: Option<Vec3>,
         focus_data: Option<FocusData>,
+        shutter: (f32, f32),
     ) -> Self {
         let h: f32 = f32::tan(vertical_fov * std::f32::consts::PI / 360.0f32);
 
@@ -126,27 +140,31 @@ impl Camera {
             u,
             v,
             lens_radius: focus_data.map(|x| x.aperture / 2.0f32),
+            shutter,
         }
     }
 
     pub fn get_ray_from_coords(&self, c_u: f32, c_v: f32) -> Ray {
+        let time = rand_range_f32(self.shutter.0, self.shutter.1);
         match self.lens_radius {
             // if there is Some lens-radius, we need to compute defocus blur (or depth of field)
             Some(lens_radius) => {
                 let rd = lens_radius * random_in_unit_disk();
                 let offset = self.u * rd.x + self.v * rd.y;
 
-                Ray::new(
+                Ray::with_time(
                     self.origin + offset,
                     self.lower_left_corner + c_u * self.horizontal + c_v * self.vertical
                         - self.origin
                         - offset,
+                    time,
                 )
             }
             // else we cast a normal ray from a single point
-            None => Ray::new(
+            None => Ray::with_time(
                 self.origin,
                 self.lower_left_corner + c_u * self.horizontal + c_v * self.vertical - self.origin,
+                time,
             ),
         }
     }
@@ -167,6 +185,7 @@ impl Default for CameraBuilder {
             vertical_fov: 40.0,
             aspect_ratio: 16.0 / 9.0,
             focus_data: None,
+            shutter: (0.0, 0.0),
         }
     }
 }
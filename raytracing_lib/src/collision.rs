@@ -1,23 +1,42 @@
 use crate::aabb::AABB;
 use crate::material::Material;
 use crate::ray::Ray;
+use crate::utils::orthonormal_basis;
 use derive_more::Display;
-use nalgebra_glm::{dot, Vec3};
+use nalgebra_glm::{cross, dot, length2, normalize, Vec3};
+use rand::seq::SliceRandom;
 use std::sync::Arc;
 
 #[derive(Display)]
 #[display(
-    fmt = "Hit Record, point:{:?}, normal:{:?}, t:{:?}, front_face:{:?}",
+    fmt = "Hit Record, point:{:?}, normal:{:?}, t:{:?}, u:{:?}, v:{:?}, front_face:{:?}",
     point,
     normal,
     t,
+    u,
+    v,
     front_face
 )]
 pub struct HitRecord {
     pub point: Vec3,
+    // shading normal, possibly interpolated or perturbed by the material, on the side of the ray
     pub normal: Vec3,
+    // normal of the actual surface, on the side of the ray. Scattered rays must stay on the right
+    // side of it, whatever the shading normal says
+    pub geometric_normal: Vec3,
+    // directions of increasing u and v on the surface, forming an orthonormal basis with normal
+    pub tangent: Vec3,
+    pub bitangent: Vec3,
+    // derivatives of the point along u and v, zero for surfaces that do not parametrize their uv
+    pub dpdu: Vec3,
+    pub dpdv: Vec3,
     pub t: f32,
+    // surface coordinates of the hit point, used to look up textures
+    pub u: f32,
+    pub v: f32,
     pub front_face: bool,
+    // color interpolated from the vertices of a mesh, it tints the albedo of the material
+    pub vertex_color: Option<Vec3>,
     pub material_hit: Arc<Box<dyn Material>>,
 }
 
@@ -32,6 +51,8 @@ impl HitRecord {
         r: &Ray,
         t: f32,
         outward_normal: &Vec3,
+        u: f32,
+        v: f32,
         material_hit: Arc<Box<dyn Material>>,
     ) -> Self {
         let front_face = dot(&r.direction, outward_normal) < 0f32;
@@ -40,18 +61,88 @@ impl HitRecord {
         } else {
             -(*outward_normal)
         };
+        let (tangent, bitangent) = orthonormal_basis(&normal);
         HitRecord {
             point: r.at(t),
             normal,
+            geometric_normal: normal,
+            tangent,
+            bitangent,
+            dpdu: Vec3::new(0.0, 0.0, 0.0),
+            dpdv: Vec3::new(0.0, 0.0, 0.0),
             t,
+            u,
+            v,
             front_face,
+            vertex_color: None,
             material_hit,
         }
     }
+
+    pub fn with_uv_derivatives(mut self, dpdu: Vec3, dpdv: Vec3) -> Self {
+        self.dpdu = dpdu;
+        self.dpdv = dpdv;
+        self
+    }
+
+    pub fn with_vertex_color(mut self, color: Vec3) -> Self {
+        self.vertex_color = Some(color);
+        self
+    }
+
+    // multiplies an albedo by the vertex color, if any
+    pub fn tint(&self, albedo: Vec3) -> Vec3 {
+        match &self.vertex_color {
+            Some(color) => albedo.component_mul(color),
+            None => albedo,
+        }
+    }
+
+    // sets the shading normal (given for the outward side of the surface, like in new) and the
+    // tangent, which only needs to roughly follow the direction of increasing u
+    pub fn with_shading_frame(mut self, outward_normal: &Vec3, tangent: &Vec3) -> Self {
+        self.normal = if self.front_face {
+            *outward_normal
+        } else {
+            -(*outward_normal)
+        };
+        self.tangent = *tangent;
+        self.orthonormalize_frame();
+        self
+    }
+
+    // replaces the shading normal, already on the side of the ray, keeping the tangent as close as
+    // possible to the previous one
+    pub fn set_shading_normal(&mut self, normal: &Vec3) {
+        self.normal = normalize(normal);
+        self.orthonormalize_frame();
+    }
+
+    fn orthonormalize_frame(&mut self) {
+        let tangent = self.tangent - self.normal * dot(&self.normal, &self.tangent);
+        self.tangent = if length2(&tangent) > 1e-12 {
+            normalize(&tangent)
+        } else {
+            orthonormal_basis(&self.normal).0
+        };
+        self.bitangent = cross(&self.normal, &self.tangent);
+    }
 }
 pub trait Hittable: Send + Sync {
     fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord>;
     fn bounding_box(&self, t0: f32, t1: f32) -> Option<AABB>;
+    // returns the probability density, over the sphere of directions seen from origin, of
+    // `random` returning the given direction
+    #[allow(unused_variables)]
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> f32 {
+        0.0
+    }
+    // returns a random direction from origin toward the object, None if the object cannot be
+    // sampled this way
+    #[allow(unused_variables)]
+    fn random(&self, origin: &Vec3) -> Option<Vec3> {
+        None
+    }
 }
 
 #[derive(Default)]
@@ -74,6 +165,10 @@ impl HittableList {
     pub fn clear(&mut self) {
         self.hittables.clear();
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.hittables.is_empty()
+    }
 }
 
 impl Hittable for HittableList {
@@ -114,4 +209,23 @@ impl Hittable for HittableList {
 
         output_box
     }
+
+    // sampling the list picks one of its objects uniformly, the density is then the mean of the densities
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> f32 {
+        if self.hittables.is_empty() {
+            return 0.0;
+        }
+        let sum: f32 = self
+            .hittables
+            .iter()
+            .map(|object| object.pdf_value(origin, direction))
+            .sum();
+        sum / self.hittables.len() as f32
+    }
+
+    fn random(&self, origin: &Vec3) -> Option<Vec3> {
+        self.hittables
+            .choose(&mut rand::thread_rng())
+            .and_then(|object| object.random(origin))
+    }
 }
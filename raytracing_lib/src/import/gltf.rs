@@ -0,0 +1,800 @@
+use crate::camera::Camera;
+use crate::collision::{Hittable, HittableList};
+use crate::material::{Material, MetallicRoughness, NormalPerturbation};
+use crate::object::{Transform, TriangleMesh};
+use crate::texture::{self, ImageTexture, Texture};
+use crate::world::{World, WorldBuilder};
+use crate::MaterialAtlas;
+use nalgebra_glm::{Mat4, Vec2, Vec3, Vec4};
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+// everything a glTF file describes that can be rendered
+pub struct GltfScene {
+    pub world: World,
+    // materials are registered under their glTF name, or "material_<index>" if they have none,
+    // names already taken are suffixed with "_<index>"
+    pub atlas: MaterialAtlas,
+    // perspective cameras placed by the nodes referencing them, in the order they are found
+    pub cameras: Vec<Arc<Camera>>,
+}
+
+// Loads a glTF 2.0 file, either a .gltf JSON file with its buffers and images embedded as data
+// URIs or next to it, or a binary .glb file. The meshes of the nodes of the default scene become
+// triangle meshes, shared by all the nodes using them through transformed instances. Materials
+// use the metallic-roughness model with their base color, metallic-roughness, normal and emissive
+// textures.
+// Not supported: orthographic cameras, texture coordinate sets other than the first one, texture
+// transforms, alpha blending and masking, sparse accessors, skins and morph targets, and
+// primitives other than triangles.
+pub fn load_gltf<P: AsRef<Path>>(path: &P) -> Result<GltfScene, String> {
+    let path = path.as_ref();
+    let content = std::fs::read(path)
+        .map_err(|err| format!("Cannot load glTF file {}: {}", path.display(), err))?;
+    let in_file = |err: String| format!("glTF file {}: {}", path.display(), err);
+
+    let (json, binary_chunk) = if content.starts_with(b"glTF") {
+        split_glb(&content).map_err(in_file)?
+    } else {
+        (&content[..], None)
+    };
+    let json = std::str::from_utf8(json).map_err(|_| in_file("invalid JSON".into()))?;
+    let document: Document = serde_json::from_str(json).map_err(|err| in_file(err.to_string()))?;
+    let directory = path.parent().unwrap_or_else(|| Path::new("."));
+
+    let mut loader = Loader {
+        document: &document,
+        directory,
+        buffers: Vec::new(),
+        textures: HashMap::new(),
+    };
+    loader.load_buffers(binary_chunk).map_err(in_file)?;
+    let mut atlas = MaterialAtlas::default();
+    let materials = loader.load_materials(&mut atlas).map_err(in_file)?;
+    let meshes = loader.load_meshes(&materials).map_err(in_file)?;
+
+    let mut world_builder = World::builder();
+    let mut cameras = Vec::new();
+    let roots = match document.scenes.get(document.scene.unwrap_or(0)) {
+        Some(scene) => scene.nodes.clone(),
+        // without scenes, the nodes that are nobody's children are the roots
+        None => (0..document.nodes.len())
+            .filter(|node| {
+                !document
+                    .nodes
+                    .iter()
+                    .any(|other| other.children.contains(node))
+            })
+            .collect(),
+    };
+    for root in roots {
+        loader
+            .add_node(
+                root,
+                &Mat4::identity(),
+                0,
+                &meshes,
+                &mut world_builder,
+                &mut cameras,
+            )
+            .map_err(in_file)?;
+    }
+
+    Ok(GltfScene {
+        world: world_builder.build(),
+        atlas,
+        cameras,
+    })
+}
+
+// a GLB file is a 12 bytes header followed by a JSON chunk and an optional binary chunk, each
+// starting with their length and type
+fn split_glb(content: &[u8]) -> Result<(&[u8], Option<&[u8]>), String> {
+    let word = |offset: usize| -> Result<usize, String> {
+        content
+            .get(offset..offset + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+            .ok_or_else(|| String::from("truncated GLB file"))
+    };
+    if word(4)? != 2 {
+        return Err(format!("unsupported GLB version {}", word(4)?));
+    }
+    let mut json = None;
+    let mut binary = None;
+    let mut offset = 12;
+    while offset + 8 <= content.len() {
+        let (length, kind) = (word(offset)?, word(offset + 4)?);
+        let chunk = content
+            .get(offset + 8..offset + 8 + length)
+            .ok_or_else(|| String::from("truncated GLB chunk"))?;
+        match kind {
+            0x4E4F_534A => json = json.or(Some(chunk)),
+            0x004E_4942 => binary = binary.or(Some(chunk)),
+            // unknown chunks must be ignored
+            _ => {}
+        }
+        offset += 8 + length;
+    }
+    Ok((
+        json.ok_or_else(|| String::from("missing JSON chunk"))?,
+        binary,
+    ))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Document {
+    scene: Option<usize>,
+    #[serde(default)]
+    scenes: Vec<SceneNodes>,
+    #[serde(default)]
+    nodes: Vec<Node>,
+    #[serde(default)]
+    meshes: Vec<Mesh>,
+    #[serde(default)]
+    accessors: Vec<Accessor>,
+    #[serde(default)]
+    buffer_views: Vec<BufferView>,
+    #[serde(default)]
+    buffers: Vec<Buffer>,
+    #[serde(default)]
+    materials: Vec<GltfMaterial>,
+    #[serde(default)]
+    textures: Vec<GltfTexture>,
+    #[serde(default)]
+    images: Vec<Image>,
+    #[serde(default)]
+    samplers: Vec<Sampler>,
+    #[serde(default)]
+    cameras: Vec<GltfCamera>,
+}
+
+#[derive(Deserialize)]
+struct SceneNodes {
+    #[serde(default)]
+    nodes: Vec<usize>,
+}
+
+#[derive(Deserialize)]
+struct Node {
+    #[serde(default)]
+    children: Vec<usize>,
+    mesh: Option<usize>,
+    camera: Option<usize>,
+    // column major
+    matrix: Option<[f32; 16]>,
+    translation: Option<[f32; 3]>,
+    // quaternion as x, y, z, w
+    rotation: Option<[f32; 4]>,
+    scale: Option<[f32; 3]>,
+}
+
+#[derive(Deserialize)]
+struct Mesh {
+    name: Option<String>,
+    primitives: Vec<Primitive>,
+}
+
+#[derive(Deserialize)]
+struct Primitive {
+    attributes: HashMap<String, usize>,
+    indices: Option<usize>,
+    material: Option<usize>,
+    #[serde(default = "default_mode")]
+    mode: u32,
+}
+
+fn default_mode() -> u32 {
+    TRIANGLES
+}
+
+const TRIANGLES: u32 = 4;
+const TRIANGLE_STRIP: u32 = 5;
+const TRIANGLE_FAN: u32 = 6;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Accessor {
+    buffer_view: Option<usize>,
+    #[serde(default)]
+    byte_offset: usize,
+    component_type: u32,
+    #[serde(default)]
+    normalized: bool,
+    count: usize,
+    #[serde(rename = "type")]
+    kind: String,
+    sparse: Option<IgnoredAny>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BufferView {
+    buffer: usize,
+    #[serde(default)]
+    byte_offset: usize,
+    byte_length: usize,
+    byte_stride: Option<usize>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Buffer {
+    uri: Option<String>,
+    byte_length: usize,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GltfMaterial {
+    name: Option<String>,
+    #[serde(default)]
+    pbr_metallic_roughness: Pbr,
+    normal_texture: Option<TextureInfo>,
+    emissive_texture: Option<TextureInfo>,
+    #[serde(default)]
+    emissive_factor: [f32; 3],
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct Pbr {
+    base_color_factor: Option<[f32; 4]>,
+    base_color_texture: Option<TextureInfo>,
+    metallic_factor: Option<f32>,
+    roughness_factor: Option<f32>,
+    metallic_roughness_texture: Option<TextureInfo>,
+}
+
+#[derive(Deserialize)]
+struct TextureInfo {
+    index: usize,
+}
+
+#[derive(Deserialize)]
+struct GltfTexture {
+    source: Option<usize>,
+    sampler: Option<usize>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Image {
+    uri: Option<String>,
+    buffer_view: Option<usize>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Sampler {
+    mag_filter: Option<u32>,
+    wrap_s: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct GltfCamera {
+    perspective: Option<Perspective>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Perspective {
+    // radians
+    yfov: f32,
+    aspect_ratio: Option<f32>,
+}
+
+struct Loader<'a> {
+    document: &'a Document,
+    directory: &'a Path,
+    buffers: Vec<Vec<u8>>,
+    // textures already decoded, with whether they hold sRGB colors
+    textures: HashMap<(usize, bool), Arc<dyn Texture>>,
+}
+
+impl Loader<'_> {
+    fn load_buffers(&mut self, binary_chunk: Option<&[u8]>) -> Result<(), String> {
+        for (index, buffer) in self.document.buffers.iter().enumerate() {
+            let data = match (&buffer.uri, binary_chunk) {
+                (Some(uri), _) => self.read_uri(uri)?,
+                // the first buffer of a GLB file without uri is its binary chunk
+                (None, Some(chunk)) if index == 0 => chunk.to_vec(),
+                (None, _) => return Err(format!("buffer {} has no data", index)),
+            };
+            if data.len() < buffer.byte_length {
+                return Err(format!(
+                    "buffer {} has {} bytes instead of {}",
+                    index,
+                    data.len(),
+                    buffer.byte_length
+                ));
+            }
+            self.buffers.push(data);
+        }
+        Ok(())
+    }
+
+    // data URIs are decoded, other URIs are paths relative to the glTF file
+    fn read_uri(&self, uri: &str) -> Result<Vec<u8>, String> {
+        if let Some(data) = uri.strip_prefix("data:") {
+            let (_, encoded) = data
+                .split_once(";base64,")
+                .ok_or_else(|| String::from("only base64 data URIs are supported"))?;
+            return decode_base64(encoded);
+        }
+        let path = self.directory.join(percent_decode(uri));
+        std::fs::read(&path).map_err(|err| format!("cannot load {}: {}", path.display(), err))
+    }
+
+    fn buffer_view(&self, index: usize) -> Result<(&[u8], Option<usize>), String> {
+        let view = self
+            .document
+            .buffer_views
+            .get(index)
+            .ok_or_else(|| format!("unknown buffer view {}", index))?;
+        let data = self
+            .buffers
+            .get(view.buffer)
+            .zip(view.byte_offset.checked_add(view.byte_length))
+            .and_then(|(buffer, end)| buffer.get(view.byte_offset..end))
+            .ok_or_else(|| format!("buffer view {} is out of its buffer", index))?;
+        Ok((data, view.byte_stride))
+    }
+
+    // values of the elements of an accessor, one Vec per element, normalized integers are
+    // brought back to [0, 1] or [-1, 1]. The elements must have one of the numbers of components
+    // expected by the attribute.
+    fn read_accessor(&self, index: usize, expected: &[usize]) -> Result<Vec<Vec<f64>>, String> {
+        let accessor = self
+            .document
+            .accessors
+            .get(index)
+            .ok_or_else(|| format!("unknown accessor {}", index))?;
+        if accessor.sparse.is_some() {
+            return Err(format!("sparse accessor {} is not supported", index));
+        }
+        let components = match accessor.kind.as_str() {
+            "SCALAR" => 1,
+            "VEC2" => 2,
+            "VEC3" => 3,
+            "VEC4" => 4,
+            kind => return Err(format!("unsupported accessor type {}", kind)),
+        };
+        if !expected.contains(&components) {
+            return Err(format!(
+                "accessor {} of type {} cannot be used here",
+                index, accessor.kind
+            ));
+        }
+        let (size, max): (usize, f64) = match accessor.component_type {
+            5120 => (1, i8::MAX as f64),
+            5121 => (1, u8::MAX as f64),
+            5122 => (2, i16::MAX as f64),
+            5123 => (2, u16::MAX as f64),
+            5125 => (4, u32::MAX as f64),
+            5126 => (4, 1.0),
+            kind => return Err(format!("unknown component type {}", kind)),
+        };
+        // accessors without buffer view are only filled by sparse values
+        let buffer_view = accessor
+            .buffer_view
+            .ok_or_else(|| format!("accessor {} has no buffer view", index))?;
+        let (data, stride) = self.buffer_view(buffer_view)?;
+        let element_size = components * size;
+        let stride = stride.unwrap_or(element_size);
+        if stride < element_size {
+            return Err(format!(
+                "accessor {} has elements of {} bytes every {} bytes",
+                index, element_size, stride
+            ));
+        }
+        // the count comes from the file, it is checked against the data before allocating
+        let end = match accessor.count.checked_sub(1) {
+            Some(last) => last
+                .checked_mul(stride)
+                .and_then(|offset| offset.checked_add(accessor.byte_offset))
+                .and_then(|offset| offset.checked_add(element_size)),
+            None => Some(0),
+        };
+        if !matches!(end, Some(end) if end <= data.len()) {
+            return Err(format!("accessor {} is out of its buffer view", index));
+        }
+
+        let mut elements = Vec::with_capacity(accessor.count);
+        for element in 0..accessor.count {
+            let start = accessor.byte_offset + element * stride;
+            let values = data[start..start + element_size]
+                .chunks_exact(size)
+                .map(|b| {
+                    let value = match accessor.component_type {
+                        5120 => b[0] as i8 as f64,
+                        5121 => b[0] as f64,
+                        5122 => i16::from_le_bytes([b[0], b[1]]) as f64,
+                        5123 => u16::from_le_bytes([b[0], b[1]]) as f64,
+                        5125 => u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+                        _ => f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+                    };
+                    if accessor.normalized {
+                        (value / max).max(-1.0)
+                    } else {
+                        value
+                    }
+                })
+                .collect();
+            elements.push(values);
+        }
+        Ok(elements)
+    }
+
+    fn texture(&mut self, index: usize, srgb: bool) -> Result<Arc<dyn Texture>, String> {
+        if let Some(texture) = self.textures.get(&(index, srgb)) {
+            return Ok(Arc::clone(texture));
+        }
+        let gltf_texture = self
+            .document
+            .textures
+            .get(index)
+            .ok_or_else(|| format!("unknown texture {}", index))?;
+        let image = gltf_texture
+            .source
+            .and_then(|source| self.document.images.get(source))
+            .ok_or_else(|| format!("texture {} has no image", index))?;
+        let mut texture = match (&image.uri, image.buffer_view) {
+            (Some(uri), _) => ImageTexture::from_memory(&self.read_uri(uri)?, srgb)?,
+            (None, Some(view)) => ImageTexture::from_memory(self.buffer_view(view)?.0, srgb)?,
+            (None, None) => return Err(format!("image of texture {} has no data", index)),
+        };
+        if let Some(sampler) = gltf_texture
+            .sampler
+            .and_then(|sampler| self.document.samplers.get(sampler))
+        {
+            texture = texture.wrap(match sampler.wrap_s {
+                Some(33071) => texture::Wrap::Clamp,
+                Some(33648) => texture::Wrap::Mirror,
+                _ => texture::Wrap::Repeat,
+            });
+            if sampler.mag_filter == Some(9728) {
+                texture = texture.filter(texture::Filter::Nearest);
+            }
+        }
+        let texture: Arc<dyn Texture> = Arc::new(texture);
+        self.textures.insert((index, srgb), Arc::clone(&texture));
+        Ok(texture)
+    }
+
+    // returns the materials in the order of the document, they are also added to the atlas
+    fn load_materials(
+        &mut self,
+        atlas: &mut MaterialAtlas,
+    ) -> Result<Vec<Arc<Box<dyn Material>>>, String> {
+        let mut materials = Vec::with_capacity(self.document.materials.len());
+        for (index, gltf_material) in self.document.materials.iter().enumerate() {
+            let in_material = |err: String| format!("material {}: {}", index, err);
+            let pbr = &gltf_material.pbr_metallic_roughness;
+            let [r, g, b, _] = pbr.base_color_factor.unwrap_or([1.0; 4]);
+            let base_color = scaled(
+                Vec3::new(r, g, b),
+                match &pbr.base_color_texture {
+                    Some(info) => Some(self.texture(info.index, true).map_err(in_material)?),
+                    None => None,
+                },
+            );
+            let mut material = MetallicRoughness::with_texture(
+                base_color,
+                pbr.metallic_factor.unwrap_or(1.0),
+                pbr.roughness_factor.unwrap_or(1.0),
+            );
+            if let Some(info) = &pbr.metallic_roughness_texture {
+                material.metallic_roughness =
+                    Some(self.texture(info.index, false).map_err(in_material)?);
+            }
+            if let Some(info) = &gltf_material.normal_texture {
+                material.normal_perturbation = Some(NormalPerturbation::NormalMap(
+                    self.texture(info.index, false).map_err(in_material)?,
+                ));
+            }
+            let emissive_factor = Vec3::from(gltf_material.emissive_factor);
+            if emissive_factor != Vec3::new(0.0, 0.0, 0.0) {
+                material.emissive = Some(scaled(
+                    emissive_factor,
+                    match &gltf_material.emissive_texture {
+                        Some(info) => Some(self.texture(info.index, true).map_err(in_material)?),
+                        None => None,
+                    },
+                ));
+            }
+
+            let name = match &gltf_material.name {
+                Some(name) => name.clone(),
+                None => format!("material_{}", index),
+            };
+            // names are not unique in glTF files, later materials are suffixed with their index
+            let name = match atlas.get_material(&name) {
+                Some(_) => (index..)
+                    .map(|suffix| format!("{}_{}", name, suffix))
+                    .find(|suffixed| atlas.get_material(suffixed).is_none())
+                    .unwrap_or(name),
+                None => name,
+            };
+            atlas.insert_material(&name, material);
+            materials.push(
+                atlas
+                    .get_material(&name)
+                    .ok_or_else(|| format!("Cannot find material {}", name))?,
+            );
+        }
+        Ok(materials)
+    }
+
+    // returns each mesh as a single object made of the meshes of its primitives
+    fn load_meshes(
+        &self,
+        materials: &[Arc<Box<dyn Material>>],
+    ) -> Result<Vec<Arc<dyn Hittable>>, String> {
+        // the material of primitives without one, as defined by the specification
+        let default_material: Arc<Box<dyn Material>> =
+            Arc::new(Box::new(MetallicRoughness::default()));
+        let mut meshes = Vec::with_capacity(self.document.meshes.len());
+        for (index, mesh) in self.document.meshes.iter().enumerate() {
+            let in_mesh = |err: String| match &mesh.name {
+                Some(name) => format!("mesh {}: {}", name, err),
+                None => format!("mesh {}: {}", index, err),
+            };
+            let mut parts = HittableList::new();
+            let mut part_count = 0;
+            let mut single_part = None;
+            for primitive in mesh.primitives.iter() {
+                let material = match primitive.material {
+                    Some(material) => materials
+                        .get(material)
+                        .map(Arc::clone)
+                        .ok_or_else(|| in_mesh(format!("unknown material {}", material)))?,
+                    None => Arc::clone(&default_material),
+                };
+                if let Some(part) = self.load_primitive(primitive, material).map_err(in_mesh)? {
+                    let part: Arc<dyn Hittable> = Arc::new(part);
+                    parts.add_hittable(Arc::clone(&part));
+                    single_part = Some(part);
+                    part_count += 1;
+                }
+            }
+            meshes.push(match (part_count, single_part) {
+                (1, Some(part)) => part,
+                _ => Arc::new(parts),
+            });
+        }
+        Ok(meshes)
+    }
+
+    // returns None for primitives that are not made of triangles
+    fn load_primitive(
+        &self,
+        primitive: &Primitive,
+        material: Arc<Box<dyn Material>>,
+    ) -> Result<Option<TriangleMesh>, String> {
+        if !matches!(primitive.mode, TRIANGLES | TRIANGLE_STRIP | TRIANGLE_FAN) {
+            return Ok(None);
+        }
+        let attribute = |name: &str| primitive.attributes.get(name).cloned();
+        let position = attribute("POSITION")
+            .ok_or_else(|| String::from("primitive without POSITION attribute"))?;
+        let positions: Vec<Vec3> = self
+            .read_accessor(position, &[3])?
+            .iter()
+            .map(|p| vector3(p))
+            .collect();
+        let normals = match attribute("NORMAL") {
+            Some(accessor) => Some(
+                self.read_accessor(accessor, &[3])?
+                    .iter()
+                    .map(|n| vector3(n))
+                    .collect(),
+            ),
+            None => None,
+        };
+        // glTF textures have v going down from the top of the image
+        let uvs = match attribute("TEXCOORD_0") {
+            Some(accessor) => Some(
+                self.read_accessor(accessor, &[2])?
+                    .iter()
+                    .map(|uv| Vec2::new(uv[0] as f32, 1.0 - uv[1] as f32))
+                    .collect(),
+            ),
+            None => None,
+        };
+        // RGB or RGBA, alpha is ignored
+        let colors = match attribute("COLOR_0") {
+            Some(accessor) => Some(
+                self.read_accessor(accessor, &[3, 4])?
+                    .iter()
+                    .map(|c| vector3(c))
+                    .collect(),
+            ),
+            None => None,
+        };
+
+        let vertices: Vec<usize> = match primitive.indices {
+            Some(accessor) => self
+                .read_accessor(accessor, &[1])?
+                .iter()
+                .map(|i| i[0] as usize)
+                .collect(),
+            None => (0..positions.len()).collect(),
+        };
+        let indices = match primitive.mode {
+            TRIANGLE_STRIP => (2..vertices.len())
+                .map(|i| {
+                    // every other triangle is flipped to keep the winding
+                    if i % 2 == 0 {
+                        [vertices[i - 2], vertices[i - 1], vertices[i]]
+                    } else {
+                        [vertices[i - 1], vertices[i - 2], vertices[i]]
+                    }
+                })
+                .collect(),
+            TRIANGLE_FAN => (2..vertices.len())
+                .map(|i| [vertices[0], vertices[i - 1], vertices[i]])
+                .collect(),
+            _ => vertices
+                .chunks_exact(3)
+                .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+                .collect::<Vec<_>>(),
+        };
+        if indices.is_empty() {
+            return Ok(None);
+        }
+        TriangleMesh::new(positions, indices, normals, uvs, colors, material).map(Some)
+    }
+
+    fn add_node(
+        &self,
+        index: usize,
+        parent: &Mat4,
+        depth: usize,
+        meshes: &[Arc<dyn Hittable>],
+        world_builder: &mut WorldBuilder,
+        cameras: &mut Vec<Arc<Camera>>,
+    ) -> Result<(), String> {
+        // a valid hierarchy is a forest, deeper than the number of nodes means a cycle
+        if depth > self.document.nodes.len() {
+            return Err("cycle in the node hierarchy".into());
+        }
+        let node = self
+            .document
+            .nodes
+            .get(index)
+            .ok_or_else(|| format!("unknown node {}", index))?;
+        let matrix = parent * node_matrix(node);
+
+        if let Some(mesh) = node.mesh {
+            let mesh = meshes
+                .get(mesh)
+                .ok_or_else(|| format!("node {} references an unknown mesh {}", index, mesh))?;
+            if matrix == Mat4::identity() {
+                world_builder.add_shared_object(Arc::clone(mesh));
+            } else {
+                let instance = Transform::new(Arc::clone(mesh), matrix)
+                    .map_err(|err| format!("node {}: {}", index, err))?;
+                world_builder.add_object(instance);
+            }
+        }
+        if let Some(perspective) = node
+            .camera
+            .and_then(|camera| self.document.cameras.get(camera))
+            .and_then(|camera| camera.perspective.as_ref())
+        {
+            // cameras look toward -z with y up in their node
+            let point = |p: Vec4| {
+                let p = matrix * p;
+                Vec3::new(p.x, p.y, p.z)
+            };
+            let mut camera = Camera::builder()
+                .set_origin(point(Vec4::new(0.0, 0.0, 0.0, 1.0)))
+                .set_look_at(point(Vec4::new(0.0, 0.0, -1.0, 1.0)))
+                .set_v_up(point(Vec4::new(0.0, 1.0, 0.0, 0.0)))
+                .set_vertical_fov(perspective.yfov.to_degrees());
+            if let Some(aspect_ratio) = perspective.aspect_ratio {
+                camera = camera.set_aspect_ratio(aspect_ratio);
+            }
+            cameras.push(camera.build());
+        }
+
+        for &child in node.children.iter() {
+            self.add_node(child, &matrix, depth + 1, meshes, world_builder, cameras)?;
+        }
+        Ok(())
+    }
+}
+
+fn vector3(values: &[f64]) -> Vec3 {
+    Vec3::new(values[0] as f32, values[1] as f32, values[2] as f32)
+}
+
+// local transform of a node, either a matrix or a translation, rotation and scale
+fn node_matrix(node: &Node) -> Mat4 {
+    if let Some(matrix) = &node.matrix {
+        return Mat4::from_column_slice(matrix);
+    }
+    let mut matrix = Mat4::identity();
+    if let Some(translation) = &node.translation {
+        matrix *= nalgebra_glm::translation(&Vec3::from(*translation));
+    }
+    if let Some([x, y, z, w]) = node.rotation {
+        matrix *= nalgebra_glm::quat_to_mat4(&nalgebra_glm::quat(x, y, z, w));
+    }
+    if let Some(scale) = &node.scale {
+        matrix *= nalgebra_glm::scaling(&Vec3::from(*scale));
+    }
+    matrix
+}
+
+// glTF multiplies textures by constant factors
+struct Scaled {
+    factor: Vec3,
+    texture: Arc<dyn Texture>,
+}
+
+impl Texture for Scaled {
+    fn value(&self, u: f32, v: f32, point: &Vec3) -> Vec3 {
+        self.texture.value(u, v, point).component_mul(&self.factor)
+    }
+}
+
+fn scaled(factor: Vec3, texture: Option<Arc<dyn Texture>>) -> Arc<dyn Texture> {
+    match texture {
+        Some(texture) if factor == Vec3::new(1.0, 1.0, 1.0) => texture,
+        Some(texture) => Arc::new(Scaled { factor, texture }),
+        None => Arc::new(texture::Constant::new(factor)),
+    }
+}
+
+fn decode_base64(encoded: &str) -> Result<Vec<u8>, String> {
+    let mut data = Vec::with_capacity(encoded.len() * 3 / 4);
+    let mut bits = 0u32;
+    let mut bit_count = 0;
+    for c in encoded.bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' => break,
+            _ => return Err(format!("invalid base64 character {}", c as char)),
+        };
+        bits = (bits << 6) | value as u32;
+        bit_count += 6;
+        if bit_count >= 8 {
+            bit_count -= 8;
+            data.push((bits >> bit_count) as u8);
+        }
+    }
+    Ok(data)
+}
+
+// URIs of files can have escaped characters, like %20 for spaces
+fn percent_decode(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], escaped) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
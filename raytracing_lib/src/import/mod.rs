@@ -0,0 +1,9 @@
+mod gltf;
+mod obj;
+mod ply;
+mod stl;
+
+pub use gltf::{load_gltf, GltfScene};
+pub use obj::{load_obj, load_obj_meshes};
+pub use ply::load_ply;
+pub use stl::load_stl;
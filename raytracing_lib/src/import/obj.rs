@@ -0,0 +1,138 @@
+use crate::material::{Dielectric, Diffuse, Material, Metal};
+use crate::object::TriangleMesh;
+use crate::world::WorldBuilder;
+use crate::MaterialAtlas;
+use nalgebra_glm::{Vec2, Vec3};
+use std::path::Path;
+use std::sync::Arc;
+
+// Loads the meshes of a Wavefront OBJ file into the world builder. Materials found in the
+// associated MTL file(s) are registered in the atlas as "<OBJ file stem>/<MTL name>", so that they
+// do not replace the materials of the scene, faces without material use the given default one.
+pub fn load_obj<P: AsRef<Path>>(
+    path: &P,
+    world_builder: &mut WorldBuilder,
+    atlas: &mut MaterialAtlas,
+    default_material: Arc<Box<dyn Material>>,
+) -> Result<(), String> {
+    for mesh in load_obj_meshes(path, atlas, default_material)? {
+        world_builder.add_object(mesh);
+    }
+    Ok(())
+}
+
+// same as load_obj but returns the meshes instead of adding them to a world
+pub fn load_obj_meshes<P: AsRef<Path>>(
+    path: &P,
+    atlas: &mut MaterialAtlas,
+    default_material: Arc<Box<dyn Material>>,
+) -> Result<Vec<TriangleMesh>, String> {
+    let path = path.as_ref();
+    let (models, materials) = tobj::load_obj(path, &tobj::GPU_LOAD_OPTIONS)
+        .map_err(|err| format!("Cannot load OBJ file {}: {}", path.display(), err))?;
+    let materials =
+        materials.map_err(|err| format!("Cannot load MTL file for {}: {}", path.display(), err))?;
+
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+    let mut mtl_materials = Vec::with_capacity(materials.len());
+    for material in materials.iter() {
+        let name = format!("{}/{}", stem, material.name);
+        insert_mtl_material(atlas, &name, material);
+        mtl_materials.push(
+            atlas
+                .get_material(&name)
+                .ok_or_else(|| format!("Cannot find material {}", name))?,
+        );
+    }
+
+    let mut meshes = Vec::with_capacity(models.len());
+    for tobj::Model { mesh, name } in models.into_iter() {
+        if mesh.indices.is_empty() {
+            continue;
+        }
+
+        let positions = mesh
+            .positions
+            .chunks_exact(3)
+            .map(|p| Vec3::new(p[0], p[1], p[2]))
+            .collect();
+        let indices = mesh
+            .indices
+            .chunks_exact(3)
+            .map(|i| [i[0] as usize, i[1] as usize, i[2] as usize])
+            .collect();
+        let normals = if mesh.normals.is_empty() {
+            None
+        } else {
+            Some(
+                mesh.normals
+                    .chunks_exact(3)
+                    .map(|n| Vec3::new(n[0], n[1], n[2]))
+                    .collect(),
+            )
+        };
+        let uvs = if mesh.texcoords.is_empty() {
+            None
+        } else {
+            Some(
+                mesh.texcoords
+                    .chunks_exact(2)
+                    .map(|uv| Vec2::new(uv[0], uv[1]))
+                    .collect(),
+            )
+        };
+        let material = match mesh.material_id {
+            Some(id) => mtl_materials
+                .get(id)
+                .map(Arc::clone)
+                .ok_or_else(|| format!("Mesh {} references an unknown material {}", name, id))?,
+            None => Arc::clone(&default_material),
+        };
+
+        let mesh = TriangleMesh::new(positions, indices, normals, uvs, None, material)
+            .map_err(|err| format!("Invalid mesh {}: {}", name, err))?;
+        meshes.push(mesh);
+    }
+
+    Ok(meshes)
+}
+
+// MTL materials are mapped onto the closest material we support:
+// - transparent materials (d < 1, or a refraction illumination model) become Dielectric using Ni
+// - materials with reflection enabled (illum 3), or with only a specular color, become Metal
+//   using Ks, the specular exponent Ns being turned into fuziness
+// - everything else is Diffuse using Kd
+fn insert_mtl_material(atlas: &mut MaterialAtlas, name: &str, material: &tobj::Material) {
+    let black = [0.0f32; 3];
+    let diffuse = material.diffuse.unwrap_or(black);
+    let specular = material.specular.unwrap_or(black);
+    let illumination_model = material.illumination_model.unwrap_or(2);
+
+    let is_transparent = material.dissolve.map(|d| d < 1.0).unwrap_or(false)
+        || matches!(illumination_model, 4 | 6 | 7 | 9);
+    let is_specular = specular != black && (illumination_model == 3 || diffuse == black);
+
+    if is_transparent {
+        atlas.insert_material(
+            name,
+            Dielectric::new(material.optical_density.unwrap_or(1.5)),
+        );
+    } else if is_specular {
+        let shininess = material.shininess.unwrap_or(0.0).clamp(0.0, 1000.0);
+        atlas.insert_material(
+            name,
+            Metal::new(
+                Vec3::new(specular[0], specular[1], specular[2]),
+                1.0 - f32::sqrt(shininess / 1000.0),
+            ),
+        );
+    } else {
+        atlas.insert_material(
+            name,
+            Diffuse::new(Vec3::new(diffuse[0], diffuse[1], diffuse[2])),
+        );
+    }
+}
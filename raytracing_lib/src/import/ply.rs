@@ -0,0 +1,356 @@
+use crate::material::Material;
+use crate::object::TriangleMesh;
+use crate::utils::srgb_to_linear;
+use nalgebra_glm::{Vec2, Vec3};
+use std::path::Path;
+use std::sync::Arc;
+
+// Loads a PLY file, ASCII or binary of either endianness, as a triangle mesh. Vertex positions,
+// normals (nx, ny, nz), texture coordinates (u and v, s and t or texture_u and texture_v) and
+// colors (red, green, blue) are read, polygonal faces are split into triangle fans and any other
+// element or property is skipped. Integer colors are assumed to be sRGB encoded and floating
+// point ones to be linear.
+pub fn load_ply<P: AsRef<Path>>(
+    path: &P,
+    material: Arc<Box<dyn Material>>,
+) -> Result<TriangleMesh, String> {
+    let path = path.as_ref();
+    let content = std::fs::read(path)
+        .map_err(|err| format!("Cannot load PLY file {}: {}", path.display(), err))?;
+    let in_file = |err: String| format!("PLY file {}: {}", path.display(), err);
+
+    let (format, elements, body) = parse_header(&content).map_err(in_file)?;
+    let mut values = match format {
+        Format::Ascii => Values::Ascii(
+            std::str::from_utf8(body)
+                .map_err(|_| in_file("invalid ASCII data".into()))?
+                .split_ascii_whitespace(),
+        ),
+        Format::BinaryLittleEndian | Format::BinaryBigEndian => Values::Binary {
+            data: body,
+            big_endian: matches!(format, Format::BinaryBigEndian),
+        },
+    };
+
+    let mut vertices = Vertices::default();
+    let mut indices = Vec::new();
+    for element in elements.iter() {
+        match element.name.as_str() {
+            "vertex" => vertices.read(element, &mut values).map_err(in_file)?,
+            "face" => read_faces(element, &mut values, &mut indices).map_err(in_file)?,
+            _ => {
+                for _ in 0..element.count {
+                    for property in element.properties.iter() {
+                        read_property(property, &mut values).map_err(in_file)?;
+                    }
+                }
+            }
+        }
+    }
+
+    let Vertices {
+        positions,
+        normals,
+        uvs,
+        colors,
+    } = vertices;
+    TriangleMesh::new(positions, indices, normals, uvs, colors, material).map_err(in_file)
+}
+
+enum Format {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Scalar {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl Scalar {
+    fn parse(name: &str) -> Result<Self, String> {
+        Ok(match name {
+            "char" | "int8" => Scalar::I8,
+            "uchar" | "uint8" => Scalar::U8,
+            "short" | "int16" => Scalar::I16,
+            "ushort" | "uint16" => Scalar::U16,
+            "int" | "int32" => Scalar::I32,
+            "uint" | "uint32" => Scalar::U32,
+            "float" | "float32" => Scalar::F32,
+            "double" | "float64" => Scalar::F64,
+            _ => return Err(format!("unknown property type {}", name)),
+        })
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            Scalar::I8 | Scalar::U8 => 1,
+            Scalar::I16 | Scalar::U16 => 2,
+            Scalar::I32 | Scalar::U32 | Scalar::F32 => 4,
+            Scalar::F64 => 8,
+        }
+    }
+
+    // brings a color component into [0, 1], integers cover the whole range of their type
+    fn color(&self, value: f64) -> f32 {
+        let max = match self {
+            Scalar::I8 => i8::MAX as f64,
+            Scalar::U8 => u8::MAX as f64,
+            Scalar::I16 => i16::MAX as f64,
+            Scalar::U16 => u16::MAX as f64,
+            Scalar::I32 => i32::MAX as f64,
+            Scalar::U32 => u32::MAX as f64,
+            Scalar::F32 | Scalar::F64 => return value as f32,
+        };
+        srgb_to_linear((value / max) as f32)
+    }
+}
+
+enum PropertyType {
+    Scalar(Scalar),
+    // number of items followed by the items
+    List { count: Scalar, item: Scalar },
+}
+
+struct Property {
+    name: String,
+    kind: PropertyType,
+}
+
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+// returns the format and elements declared by the header, and the data following it
+fn parse_header(content: &[u8]) -> Result<(Format, Vec<Element>, &[u8]), String> {
+    const END: &[u8] = b"end_header";
+    let end = content
+        .windows(END.len())
+        .position(|window| window == END)
+        .ok_or_else(|| String::from("missing end_header"))?;
+    let body_start = content[end..]
+        .iter()
+        .position(|&c| c == b'\n')
+        .map_or(content.len(), |newline| end + newline + 1);
+    let header = std::str::from_utf8(&content[..end]).map_err(|_| "invalid header")?;
+
+    let mut lines = header.lines().map(str::trim);
+    if lines.next() != Some("ply") {
+        return Err("not a PLY file".into());
+    }
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+    for line in lines {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens.as_slice() {
+            [] | ["comment", ..] | ["obj_info", ..] => {}
+            ["format", name, _version] => {
+                format = Some(match *name {
+                    "ascii" => Format::Ascii,
+                    "binary_little_endian" => Format::BinaryLittleEndian,
+                    "binary_big_endian" => Format::BinaryBigEndian,
+                    _ => return Err(format!("unknown format {}", name)),
+                })
+            }
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count
+                    .parse()
+                    .map_err(|_| format!("invalid count of {} elements", name))?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", count, item, name] => elements
+                .last_mut()
+                .ok_or_else(|| format!("property {} outside of an element", name))?
+                .properties
+                .push(Property {
+                    name: name.to_string(),
+                    kind: PropertyType::List {
+                        count: Scalar::parse(count)?,
+                        item: Scalar::parse(item)?,
+                    },
+                }),
+            ["property", kind, name] => elements
+                .last_mut()
+                .ok_or_else(|| format!("property {} outside of an element", name))?
+                .properties
+                .push(Property {
+                    name: name.to_string(),
+                    kind: PropertyType::Scalar(Scalar::parse(kind)?),
+                }),
+            _ => return Err(format!("invalid header line {}", line)),
+        }
+    }
+    let format = format.ok_or_else(|| String::from("missing format"))?;
+    Ok((format, elements, &content[body_start..]))
+}
+
+// values of the body, in the order they are declared in the header
+enum Values<'a> {
+    Ascii(std::str::SplitAsciiWhitespace<'a>),
+    Binary { data: &'a [u8], big_endian: bool },
+}
+
+impl Values<'_> {
+    fn next(&mut self, scalar: Scalar) -> Result<f64, String> {
+        match self {
+            Values::Ascii(tokens) => {
+                let token = tokens
+                    .next()
+                    .ok_or_else(|| String::from("unexpected end of file"))?;
+                token
+                    .parse()
+                    .map_err(|_| format!("invalid {:?} value {}", scalar, token))
+            }
+            Values::Binary { data, big_endian } => {
+                let size = scalar.size();
+                if data.len() < size {
+                    return Err("unexpected end of file".into());
+                }
+                let mut bytes = [0; 8];
+                bytes[..size].copy_from_slice(&data[..size]);
+                if *big_endian {
+                    bytes[..size].reverse();
+                }
+                *data = &data[size..];
+                let [b0, b1, b2, b3, ..] = bytes;
+                Ok(match scalar {
+                    Scalar::I8 => b0 as i8 as f64,
+                    Scalar::U8 => b0 as f64,
+                    Scalar::I16 => i16::from_le_bytes([b0, b1]) as f64,
+                    Scalar::U16 => u16::from_le_bytes([b0, b1]) as f64,
+                    Scalar::I32 => i32::from_le_bytes([b0, b1, b2, b3]) as f64,
+                    Scalar::U32 => u32::from_le_bytes([b0, b1, b2, b3]) as f64,
+                    Scalar::F32 => f32::from_le_bytes([b0, b1, b2, b3]) as f64,
+                    Scalar::F64 => f64::from_le_bytes(bytes),
+                })
+            }
+        }
+    }
+}
+
+// reads a property, returning the values of the items of lists
+fn read_property(property: &Property, values: &mut Values) -> Result<Vec<f64>, String> {
+    match property.kind {
+        PropertyType::Scalar(scalar) => Ok(vec![values.next(scalar)?]),
+        PropertyType::List { count, item } => {
+            let count = values.next(count)?;
+            if count < 0.0 {
+                return Err(format!("negative size of list {}", property.name));
+            }
+            (0..count as usize).map(|_| values.next(item)).collect()
+        }
+    }
+}
+
+#[derive(Default)]
+struct Vertices {
+    positions: Vec<Vec3>,
+    normals: Option<Vec<Vec3>>,
+    uvs: Option<Vec<Vec2>>,
+    colors: Option<Vec<Vec3>>,
+}
+
+impl Vertices {
+    fn read(&mut self, element: &Element, values: &mut Values) -> Result<(), String> {
+        // index of the property holding each attribute, under any of its usual names
+        let slot = |names: &[&str]| {
+            element
+                .properties
+                .iter()
+                .position(|property| names.contains(&property.name.as_str()))
+        };
+        let position = [slot(&["x"]), slot(&["y"]), slot(&["z"])];
+        let normal = [slot(&["nx"]), slot(&["ny"]), slot(&["nz"])];
+        let uv = [
+            slot(&["u", "s", "texture_u", "texture_s"]),
+            slot(&["v", "t", "texture_v", "texture_t"]),
+        ];
+        let color = [slot(&["red"]), slot(&["green"]), slot(&["blue"])];
+        if position.iter().any(Option::is_none) {
+            return Err("vertices must have x, y and z properties".into());
+        }
+        let has_normals = normal.iter().all(Option::is_some);
+        let has_uvs = uv.iter().all(Option::is_some);
+        let has_colors = color.iter().all(Option::is_some);
+
+        let mut normals = Vec::new();
+        let mut uvs = Vec::new();
+        let mut colors = Vec::new();
+        let mut vertex = vec![0.0; element.properties.len()];
+        for _ in 0..element.count {
+            for (value, property) in vertex.iter_mut().zip(element.properties.iter()) {
+                *value = match property.kind {
+                    PropertyType::Scalar(scalar) => values.next(scalar)?,
+                    // lists are not vertex attributes we know of
+                    PropertyType::List { .. } => {
+                        read_property(property, values)?;
+                        0.0
+                    }
+                };
+            }
+            let get = |slot: Option<usize>| vertex[slot.unwrap()] as f32;
+            self.positions.push(Vec3::new(
+                get(position[0]),
+                get(position[1]),
+                get(position[2]),
+            ));
+            if has_normals {
+                normals.push(Vec3::new(get(normal[0]), get(normal[1]), get(normal[2])));
+            }
+            if has_uvs {
+                uvs.push(Vec2::new(get(uv[0]), get(uv[1])));
+            }
+            if has_colors {
+                colors.push(Vec3::from_fn(|a, _| {
+                    let slot = color[a].unwrap();
+                    match element.properties[slot].kind {
+                        PropertyType::Scalar(scalar) => scalar.color(vertex[slot]),
+                        PropertyType::List { .. } => 0.0,
+                    }
+                }));
+            }
+        }
+        self.normals = Some(normals).filter(|_| has_normals);
+        self.uvs = Some(uvs).filter(|_| has_uvs);
+        self.colors = Some(colors).filter(|_| has_colors);
+        Ok(())
+    }
+}
+
+fn read_faces(
+    element: &Element,
+    values: &mut Values,
+    indices: &mut Vec<[usize; 3]>,
+) -> Result<(), String> {
+    let vertex_indices = element
+        .properties
+        .iter()
+        .position(|property| property.name == "vertex_indices" || property.name == "vertex_index")
+        .ok_or_else(|| String::from("faces must have a vertex_indices property"))?;
+    for _ in 0..element.count {
+        for (index, property) in element.properties.iter().enumerate() {
+            let items = read_property(property, values)?;
+            if index != vertex_indices {
+                continue;
+            }
+            if items.iter().any(|&i| i < 0.0) {
+                return Err("negative vertex index".into());
+            }
+            for k in 1..items.len().saturating_sub(1) {
+                indices.push([items[0] as usize, items[k] as usize, items[k + 1] as usize]);
+            }
+        }
+    }
+    Ok(())
+}
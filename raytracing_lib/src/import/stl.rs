@@ -0,0 +1,99 @@
+use crate::material::Material;
+use crate::object::TriangleMesh;
+use nalgebra_glm::Vec3;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+// Loads an STL file, ASCII or binary, as a triangle mesh. STL stores each facet with its own
+// copy of its vertices, identical ones are merged. The normals of the file are ignored, facets
+// are flat shaded.
+pub fn load_stl<P: AsRef<Path>>(
+    path: &P,
+    material: Arc<Box<dyn Material>>,
+) -> Result<TriangleMesh, String> {
+    let path = path.as_ref();
+    let content = std::fs::read(path)
+        .map_err(|err| format!("Cannot load STL file {}: {}", path.display(), err))?;
+    let in_file = |err: String| format!("STL file {}: {}", path.display(), err);
+
+    // binary files start with an 80 bytes header, which may also begin with "solid", followed by
+    // the number of facets and 50 bytes per facet
+    let is_binary = content.len() >= 84 && {
+        let count = u32::from_le_bytes([content[80], content[81], content[82], content[83]]);
+        content.len() == 84 + 50 * count as usize
+    };
+    let corners = if is_binary {
+        binary_corners(&content[84..])
+    } else if content.starts_with(b"solid") {
+        ascii_corners(&content).map_err(in_file)?
+    } else {
+        return Err(in_file("not an STL file".into()));
+    };
+
+    let mut positions = Vec::new();
+    let mut indices_of: HashMap<[u32; 3], usize> = HashMap::new();
+    let corner_indices: Vec<usize> = corners
+        .iter()
+        .map(|corner| {
+            *indices_of
+                .entry([corner.x.to_bits(), corner.y.to_bits(), corner.z.to_bits()])
+                .or_insert_with(|| {
+                    positions.push(*corner);
+                    positions.len() - 1
+                })
+        })
+        .collect();
+    let indices = corner_indices
+        .chunks_exact(3)
+        .map(|facet| [facet[0], facet[1], facet[2]])
+        .collect();
+    TriangleMesh::new(positions, indices, None, None, None, material).map_err(in_file)
+}
+
+// each facet is a normal, three vertices and a 16 bits attribute, all little endian
+fn binary_corners(facets: &[u8]) -> Vec<Vec3> {
+    let float = |bytes: &[u8]| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    facets
+        .chunks_exact(50)
+        .flat_map(|facet| {
+            (0..3).map(move |corner| {
+                let start = 12 + 12 * corner;
+                Vec3::from_fn(|a, _| float(&facet[start + 4 * a..]))
+            })
+        })
+        .collect()
+}
+
+// facets are "facet normal ... outer loop", three "vertex x y z" lines, "endloop endfacet"
+fn ascii_corners(content: &[u8]) -> Result<Vec<Vec3>, String> {
+    let text = std::str::from_utf8(content).map_err(|_| "invalid ASCII data")?;
+    let mut tokens = text.split_ascii_whitespace();
+    let mut corners = Vec::new();
+    let mut facet_corners = 0;
+    while let Some(token) = tokens.next() {
+        match token {
+            "vertex" => {
+                let mut corner = Vec3::new(0.0, 0.0, 0.0);
+                for a in 0..3 {
+                    let coordinate = tokens
+                        .next()
+                        .ok_or_else(|| String::from("unexpected end of file"))?;
+                    corner[a] = coordinate
+                        .parse()
+                        .map_err(|_| format!("invalid coordinate {}", coordinate))?;
+                }
+                corners.push(corner);
+                facet_corners += 1;
+            }
+            "endloop" => {
+                if facet_corners != 3 {
+                    return Err(format!("facet with {} vertices", facet_corners));
+                }
+                facet_corners = 0;
+            }
+            _ => {}
+        }
+    }
+    Ok(corners)
+}
@@ -1,16 +1,22 @@
 mod aabb;
+pub mod background;
 mod bvh;
 mod camera;
 mod canvas;
 mod collision;
 pub mod export;
+pub mod import;
+pub mod light;
 pub mod material;
 mod material_atlas;
 pub mod object;
 mod ray;
 mod renderer;
 pub mod scene;
+pub mod sdf;
+pub mod texture;
 mod utils;
+pub mod volume;
 mod world;
 
 pub use camera::{Camera, FocusData};
@@ -18,5 +24,5 @@ pub use canvas::Canvas;
 pub use collision::{Hittable, HittableList};
 pub use material_atlas::MaterialAtlas;
 pub use ray::Ray;
-pub use renderer::{RenderPass, Renderer};
-pub use world::World;
+pub use renderer::{MisHeuristic, RenderPass, Renderer};
+pub use world::{World, WorldBuilder};
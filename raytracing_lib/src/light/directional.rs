@@ -0,0 +1,29 @@
+use super::{Light, LightSample};
+use nalgebra_glm::{normalize, Vec3};
+
+// a light infinitely far away like the sun, all its rays are parallel
+pub struct DirectionalLight {
+    // direction in which the light travels
+    direction: Vec3,
+    irradiance: Vec3,
+}
+
+impl DirectionalLight {
+    pub fn new(direction: Vec3, irradiance: Vec3) -> Self {
+        DirectionalLight {
+            direction: normalize(&direction),
+            irradiance,
+        }
+    }
+}
+
+impl Light for DirectionalLight {
+    #[allow(unused_variables)]
+    fn sample(&self, point: &Vec3) -> Option<LightSample> {
+        Some(LightSample {
+            direction: -self.direction,
+            distance: f32::INFINITY,
+            radiance: self.irradiance,
+        })
+    }
+}
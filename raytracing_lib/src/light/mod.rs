@@ -0,0 +1,34 @@
+use nalgebra_glm::Vec3;
+
+mod directional;
+mod point;
+mod spot;
+mod sun;
+
+pub use directional::DirectionalLight;
+pub use point::PointLight;
+pub use spot::SpotLight;
+pub use sun::SunLight;
+
+pub struct LightSample {
+    // unit direction from the lit point toward the light
+    pub direction: Vec3,
+    // distance to the light, infinite for lights outside of the scene
+    pub distance: f32,
+    // light arriving at the lit point, as received by a surface facing the light
+    pub radiance: Vec3,
+}
+
+// Lights that are not part of the geometry: they cannot be hit by rays and are only found by
+// casting shadow rays toward them.
+pub trait Light: Send + Sync {
+    // returns the light arriving at the given point, None if the light does not reach it
+    fn sample(&self, point: &Vec3) -> Option<LightSample>;
+    // light seen by a ray leaving the scene in the given direction, for distant lights with a
+    // visible size. Only camera rays and specular bounces look at it, other surfaces already
+    // receive this light through sample
+    #[allow(unused_variables)]
+    fn emitted(&self, direction: &Vec3) -> Vec3 {
+        Vec3::new(0.0, 0.0, 0.0)
+    }
+}
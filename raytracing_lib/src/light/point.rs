@@ -0,0 +1,33 @@
+use super::{Light, LightSample};
+use nalgebra_glm::{length2, Vec3};
+
+// a light emitting the same intensity in all directions from a single point
+pub struct PointLight {
+    pub position: Vec3,
+    pub intensity: Vec3,
+}
+
+impl PointLight {
+    pub fn new(position: Vec3, intensity: Vec3) -> Self {
+        PointLight {
+            position,
+            intensity,
+        }
+    }
+}
+
+impl Light for PointLight {
+    fn sample(&self, point: &Vec3) -> Option<LightSample> {
+        let to_light = self.position - point;
+        let distance_squared = length2(&to_light);
+        if distance_squared <= 0.0 {
+            return None;
+        }
+        let distance = f32::sqrt(distance_squared);
+        Some(LightSample {
+            direction: to_light / distance,
+            distance,
+            radiance: self.intensity / distance_squared,
+        })
+    }
+}
@@ -0,0 +1,65 @@
+use super::{Light, LightSample};
+use nalgebra_glm::{dot, length2, normalize, Vec3};
+
+// a point light restricted to a cone: full intensity inside the inner angle, fading smoothly to
+// nothing at the outer angle
+pub struct SpotLight {
+    position: Vec3,
+    direction: Vec3,
+    intensity: Vec3,
+    cos_inner: f32,
+    cos_outer: f32,
+}
+
+impl SpotLight {
+    // angles are the half apertures of the cones, in degrees
+    pub fn new(
+        position: Vec3,
+        direction: Vec3,
+        intensity: Vec3,
+        inner_angle: f32,
+        outer_angle: f32,
+    ) -> Self {
+        let outer_angle = outer_angle.clamp(0.0, 180.0);
+        let inner_angle = inner_angle.clamp(0.0, outer_angle);
+        SpotLight {
+            position,
+            direction: normalize(&direction),
+            intensity,
+            cos_inner: f32::cos(inner_angle.to_radians()),
+            cos_outer: f32::cos(outer_angle.to_radians()),
+        }
+    }
+
+    fn falloff(&self, cos_theta: f32) -> f32 {
+        if cos_theta >= self.cos_inner {
+            return 1.0;
+        }
+        if cos_theta <= self.cos_outer {
+            return 0.0;
+        }
+        let t = (cos_theta - self.cos_outer) / (self.cos_inner - self.cos_outer);
+        t * t * (3.0 - 2.0 * t)
+    }
+}
+
+impl Light for SpotLight {
+    fn sample(&self, point: &Vec3) -> Option<LightSample> {
+        let to_light = self.position - point;
+        let distance_squared = length2(&to_light);
+        if distance_squared <= 0.0 {
+            return None;
+        }
+        let distance = f32::sqrt(distance_squared);
+        let direction = to_light / distance;
+        let falloff = self.falloff(dot(&-direction, &self.direction));
+        if falloff <= 0.0 {
+            return None;
+        }
+        Some(LightSample {
+            direction,
+            distance,
+            radiance: self.intensity * falloff / distance_squared,
+        })
+    }
+}
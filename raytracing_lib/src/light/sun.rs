@@ -0,0 +1,55 @@
+use super::{Light, LightSample};
+use crate::utils::orthonormal_basis;
+use nalgebra_glm::{dot, normalize, Vec3};
+use std::f32::consts::PI;
+
+// a disk of uniform radiance infinitely far away, like the sun: it casts soft shadows and is
+// visible to camera rays and mirror reflections
+pub struct SunLight {
+    // direction from the scene toward the sun
+    direction: Vec3,
+    radiance: Vec3,
+    cos_angular_radius: f32,
+}
+
+impl SunLight {
+    // the angular radius is half of the angle covered by the disk, in degrees
+    pub fn new(direction: Vec3, radiance: Vec3, angular_radius: f32) -> Self {
+        SunLight {
+            direction: normalize(&direction),
+            radiance,
+            cos_angular_radius: f32::cos(angular_radius.clamp(0.0, 90.0).to_radians()),
+        }
+    }
+
+    fn solid_angle(&self) -> f32 {
+        2.0 * PI * (1.0 - self.cos_angular_radius)
+    }
+}
+
+impl Light for SunLight {
+    // directions are picked uniformly over the cone covered by the disk
+    #[allow(unused_variables)]
+    fn sample(&self, point: &Vec3) -> Option<LightSample> {
+        let cos_theta = 1.0 - rand::random::<f32>() * (1.0 - self.cos_angular_radius);
+        let sin_theta = f32::sqrt(f32::max(0.0, 1.0 - cos_theta * cos_theta));
+        let phi = 2.0 * PI * rand::random::<f32>();
+        let (u, v) = orthonormal_basis(&self.direction);
+        let direction = sin_theta * f32::cos(phi) * u
+            + sin_theta * f32::sin(phi) * v
+            + cos_theta * self.direction;
+        Some(LightSample {
+            direction,
+            distance: f32::INFINITY,
+            radiance: self.radiance * self.solid_angle(),
+        })
+    }
+
+    fn emitted(&self, direction: &Vec3) -> Vec3 {
+        if dot(&normalize(direction), &self.direction) >= self.cos_angular_radius {
+            self.radiance
+        } else {
+            Vec3::new(0.0, 0.0, 0.0)
+        }
+    }
+}
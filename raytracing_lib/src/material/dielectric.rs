@@ -1,4 +1,4 @@
-use super::Material;
+use super::{Material, NormalPerturbation};
 use crate::collision::HitRecord;
 use crate::ray::Ray;
 use crate::utils::schlick;
@@ -6,6 +6,7 @@ use nalgebra_glm::{dot, normalize, reflect_vec, refract_vec, Vec3};
 
 pub struct Dielectric {
     pub refractive_index: f32,
+    pub normal_perturbation: Option<NormalPerturbation>,
     albedo: Vec3,
 }
 
@@ -13,38 +14,77 @@ impl Dielectric {
     pub fn new(refractive_index: f32) -> Self {
         Dielectric {
             refractive_index,
+            normal_perturbation: None,
             albedo: Vec3::new(1.0, 1.0, 1.0),
         }
     }
-}
 
-impl Material for Dielectric {
-    // returns None if no ray is scattered
-    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<Ray> {
-        let etai_over_etat: f32 = if hit_record.front_face {
+    pub fn normal_perturbation(mut self, normal_perturbation: NormalPerturbation) -> Self {
+        self.normal_perturbation = Some(normal_perturbation);
+        self
+    }
+
+    // reflects or refracts the unit direction about the normal facing it, returns the new
+    // direction and whether it was reflected
+    fn scatter_direction(
+        &self,
+        unit_direction: &Vec3,
+        normal: &Vec3,
+        front_face: bool,
+    ) -> (Vec3, bool) {
+        let etai_over_etat: f32 = if front_face {
             1.0 / self.refractive_index
         } else {
             self.refractive_index
         };
 
-        let unit_direction = normalize(&ray_in.direction);
-        let cos_theta = f32::min(dot(&-unit_direction, &hit_record.normal), 1.0);
+        let cos_theta = f32::min(dot(&-unit_direction, normal), 1.0);
         let sin_theta = f32::sqrt(1.0 - cos_theta * cos_theta);
         if etai_over_etat * sin_theta > 1.0f32 {
-            let reflected = reflect_vec(&unit_direction, &hit_record.normal);
-            return Some(Ray::new(hit_record.point, reflected));
+            return (reflect_vec(unit_direction, normal), true);
         }
         let reflect_prob = schlick(cos_theta, etai_over_etat);
         if rand::random::<f32>() < reflect_prob {
-            let reflected = reflect_vec(&unit_direction, &hit_record.normal);
-            return Some(Ray::new(hit_record.point, reflected));
+            return (reflect_vec(unit_direction, normal), true);
         }
-        let refracted = refract_vec(&unit_direction, &hit_record.normal, etai_over_etat);
-        Some(Ray::new(hit_record.point, refracted))
+        (refract_vec(unit_direction, normal, etai_over_etat), false)
+    }
+}
+
+impl Material for Dielectric {
+    // returns None if no ray is scattered
+    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<Ray> {
+        let unit_direction = normalize(&ray_in.direction);
+        let geometric_normal = &hit_record.geometric_normal;
+        // at grazing angles the shading normal can face away from the ray, use the actual surface
+        let normal = if dot(&unit_direction, &hit_record.normal) < 0.0 {
+            &hit_record.normal
+        } else {
+            geometric_normal
+        };
+
+        let (direction, reflected) =
+            self.scatter_direction(&unit_direction, normal, hit_record.front_face);
+        // reflected rays must stay on the side of the ray and refracted ones cross the actual
+        // surface, otherwise light would leak through it: use the geometric normal instead
+        let direction = if (dot(&direction, geometric_normal) > 0.0) == reflected {
+            direction
+        } else {
+            self.scatter_direction(&unit_direction, geometric_normal, hit_record.front_face)
+                .0
+        };
+        Some(Ray::with_time(hit_record.point, direction, ray_in.time))
+    }
+    // returns the albedo or attenuation of the surface at the hit point
+    #[allow(unused_variables)]
+    fn albedo(&self, hit_record: &HitRecord) -> Vec3 {
+        self.albedo
     }
-    // returns the albedo or attenuation of the surface
-    fn albedo(&self) -> &Vec3 {
-        &self.albedo
+
+    fn perturb_normal(&self, hit_record: &mut HitRecord) {
+        if let Some(perturbation) = &self.normal_perturbation {
+            perturbation.apply(hit_record);
+        }
     }
 }
 
@@ -52,6 +92,7 @@ impl Default for Dielectric {
     fn default() -> Self {
         Dielectric {
             refractive_index: 0.5,
+            normal_perturbation: None,
             albedo: Vec3::new(1.0, 1.0, 1.0),
         }
     }
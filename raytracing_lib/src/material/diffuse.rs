@@ -1,38 +1,83 @@
-use super::Material;
+use super::{Material, NormalPerturbation};
 use crate::collision::HitRecord;
 use crate::ray::Ray;
+use crate::texture::{Constant, Texture};
 use crate::utils::random_unit_vector;
-use nalgebra_glm::Vec3;
+use nalgebra_glm::{dot, normalize, Vec3};
+use std::f32::consts::PI;
+use std::sync::Arc;
 
 pub struct Diffuse {
-    pub albedo: Vec3,
+    pub albedo: Arc<dyn Texture>,
+    pub normal_perturbation: Option<NormalPerturbation>,
 }
 
 impl Diffuse {
     pub fn new(albedo: Vec3) -> Self {
-        Diffuse { albedo }
+        Diffuse::with_texture(Arc::new(Constant::new(albedo)))
+    }
+
+    pub fn with_texture(albedo: Arc<dyn Texture>) -> Self {
+        Diffuse {
+            albedo,
+            normal_perturbation: None,
+        }
+    }
+
+    pub fn normal_perturbation(mut self, normal_perturbation: NormalPerturbation) -> Self {
+        self.normal_perturbation = Some(normal_perturbation);
+        self
     }
 }
 
 impl Material for Diffuse {
     // returns None if no ray is scattered
-    #[allow(unused_variables)]
     fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<Ray> {
-        Some(Ray::new(
-            hit_record.point,
-            hit_record.normal + random_unit_vector(),
-        ))
+        let direction = hit_record.normal + random_unit_vector();
+        // with a perturbed normal, directions can go through the actual surface
+        if dot(&direction, &hit_record.geometric_normal) <= 0.0 {
+            return None;
+        }
+        Some(Ray::with_time(hit_record.point, direction, ray_in.time))
+    }
+    // returns the albedo or attenuation of the surface at the hit point
+    fn albedo(&self, hit_record: &HitRecord) -> Vec3 {
+        hit_record.tint(
+            self.albedo
+                .value(hit_record.u, hit_record.v, &hit_record.point),
+        )
     }
-    // returns the albedo or attenuation of the surface
-    fn albedo(&self) -> &Vec3 {
-        &self.albedo
+
+    fn perturb_normal(&self, hit_record: &mut HitRecord) {
+        if let Some(perturbation) = &self.normal_perturbation {
+            perturbation.apply(hit_record);
+        }
+    }
+
+    fn is_specular(&self) -> bool {
+        false
+    }
+    // scattered rays follow a cosine distribution around the normal
+    #[allow(unused_variables)]
+    fn scattering_pdf(&self, ray_in: &Ray, hit_record: &HitRecord, direction: &Vec3) -> f32 {
+        let cosine = dot(&hit_record.normal, &normalize(direction));
+        if cosine <= 0.0 {
+            0.0
+        } else {
+            cosine / PI
+        }
+    }
+
+    fn eval(&self, ray_in: &Ray, hit_record: &HitRecord, direction: &Vec3) -> Vec3 {
+        if dot(direction, &hit_record.geometric_normal) <= 0.0 {
+            return Vec3::new(0.0, 0.0, 0.0);
+        }
+        self.albedo(hit_record) * self.scattering_pdf(ray_in, hit_record, direction)
     }
 }
 
 impl Default for Diffuse {
     fn default() -> Self {
-        Diffuse {
-            albedo: Vec3::new(0.5, 0.5, 0.5),
-        }
+        Diffuse::new(Vec3::new(0.5, 0.5, 0.5))
     }
 }
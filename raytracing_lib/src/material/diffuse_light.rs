@@ -0,0 +1,48 @@
+use super::Material;
+use crate::collision::HitRecord;
+use crate::ray::Ray;
+use crate::texture::{Constant, Texture};
+use nalgebra_glm::Vec3;
+use std::sync::Arc;
+
+pub struct DiffuseLight {
+    pub emit: Arc<dyn Texture>,
+    albedo: Vec3,
+}
+
+impl DiffuseLight {
+    pub fn new(emit: Vec3) -> Self {
+        DiffuseLight::with_texture(Arc::new(Constant::new(emit)))
+    }
+
+    pub fn with_texture(emit: Arc<dyn Texture>) -> Self {
+        DiffuseLight {
+            emit,
+            albedo: Vec3::new(0.0, 0.0, 0.0),
+        }
+    }
+}
+
+impl Material for DiffuseLight {
+    // a light does not scatter incoming rays, it only emits
+    #[allow(unused_variables)]
+    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<Ray> {
+        None
+    }
+    // returns the albedo or attenuation of the surface at the hit point
+    #[allow(unused_variables)]
+    fn albedo(&self, hit_record: &HitRecord) -> Vec3 {
+        self.albedo
+    }
+    // emits the same radiance in all directions, from both faces of the surface
+    fn emitted(&self, hit_record: &HitRecord) -> Vec3 {
+        self.emit
+            .value(hit_record.u, hit_record.v, &hit_record.point)
+    }
+}
+
+impl Default for DiffuseLight {
+    fn default() -> Self {
+        DiffuseLight::new(Vec3::new(1.0, 1.0, 1.0))
+    }
+}
@@ -0,0 +1,242 @@
+use super::Material;
+use crate::collision::HitRecord;
+use crate::ray::Ray;
+use crate::texture::{Constant, Texture};
+use crate::utils::schlick;
+use nalgebra_glm::{dot, normalize, Vec3};
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+// A simplified version of the hair scattering model of d'Eon et al. and pbrt (Physically Based
+// Rendering, 3rd edition, section 9.9) for curves. Light is reflected by the surface of the fiber
+// (R), goes through it (TT) or is reflected inside it once or more (TRT), each time losing the
+// color it does not transmit. Each lobe is a cone around the fiber, spread by the roughness and
+// shifted by the tilt of the scales of the cuticle, and the azimuthal distributions are averaged
+// over the width of the fiber: a smooth cylinder for R, a lobe around the forward direction for
+// TT and an even spread for TRT.
+// The fiber goes along the tangent of the hit record, the direction of increasing u.
+pub struct Hair {
+    // fraction of the light kept when going once through the fiber
+    pub color: Arc<dyn Texture>,
+    // width of the lobes along the fiber, from 0 (smooth) to 1
+    pub roughness: f32,
+    // spread of the light going through the fiber around it, from 0 to 1
+    pub azimuthal_roughness: f32,
+    pub refraction_index: f32,
+    // tilt of the scales of the cuticle in degrees, moving the highlights along the fiber
+    pub scale_angle: f32,
+}
+
+// directions and lobes at a hit point
+struct Fiber {
+    axis: Vec3,
+    normal: Vec3,
+    bitangent: Vec3,
+    // longitudinal and azimuthal angles of the direction toward the origin of the ray
+    theta_in: f32,
+    phi_in: f32,
+    // fraction of the light scattered by each lobe
+    attenuations: [Vec3; 3],
+    // probability of sampling each lobe
+    probabilities: [f32; 3],
+}
+
+impl Hair {
+    pub fn new(color: Vec3, roughness: f32, azimuthal_roughness: f32) -> Self {
+        Hair::with_texture(
+            Arc::new(Constant::new(color)),
+            roughness,
+            azimuthal_roughness,
+        )
+    }
+
+    pub fn with_texture(color: Arc<dyn Texture>, roughness: f32, azimuthal_roughness: f32) -> Self {
+        Hair {
+            color,
+            roughness: roughness.clamp(0.0, 1.0),
+            azimuthal_roughness: azimuthal_roughness.clamp(0.0, 1.0),
+            refraction_index: 1.55,
+            scale_angle: 2.0,
+        }
+    }
+
+    pub fn refraction_index(mut self, refraction_index: f32) -> Self {
+        self.refraction_index = refraction_index;
+        self
+    }
+
+    pub fn scale_angle(mut self, scale_angle: f32) -> Self {
+        self.scale_angle = scale_angle;
+        self
+    }
+
+    fn fiber(&self, ray_in: &Ray, hit_record: &HitRecord) -> Fiber {
+        let (axis, normal, bitangent) =
+            (hit_record.tangent, hit_record.normal, hit_record.bitangent);
+        let toward = -normalize(&ray_in.direction);
+        let theta_in = dot(&toward, &axis).clamp(-1.0, 1.0).asin();
+        let phi_in = dot(&toward, &bitangent).atan2(dot(&toward, &normal));
+
+        let color = hit_record.tint(self.color.value(
+            hit_record.u,
+            hit_record.v,
+            &hit_record.point,
+        ));
+        let f = schlick(theta_in.cos(), self.refraction_index);
+        let white = Vec3::new(1.0, 1.0, 1.0);
+        // the TRT lobe also gathers the light reflected more than once inside the fiber
+        let multiple = (white - f * color).map(|x| 1.0 / x.max(1e-4));
+        let attenuations = [
+            f * white,
+            (1.0 - f) * (1.0 - f) * color,
+            ((1.0 - f) * (1.0 - f) * f * color.component_mul(&color)).component_mul(&multiple),
+        ];
+        let weights = attenuations.map(|a| (a.x + a.y + a.z) / 3.0);
+        let total: f32 = weights.iter().sum();
+        let probabilities = if total > 0.0 {
+            weights.map(|w| w / total)
+        } else {
+            [1.0, 0.0, 0.0]
+        };
+        Fiber {
+            axis,
+            normal,
+            bitangent,
+            theta_in,
+            phi_in,
+            attenuations,
+            probabilities,
+        }
+    }
+
+    // center and scale of the longitudinal angle of the light scattered by each lobe, around the
+    // mirror direction of the incoming light
+    fn longitudinal(&self, theta_in: f32) -> [(f32, f32); 3] {
+        let alpha = self.scale_angle.to_radians();
+        let scale = 0.01 + 0.3 * self.roughness;
+        [
+            (-theta_in + 2.0 * alpha, scale),
+            (-theta_in - alpha, scale / 2.0),
+            (-theta_in - 4.0 * alpha, 2.0 * scale),
+        ]
+    }
+
+    fn azimuthal_scale(&self) -> f32 {
+        0.05 + 0.8 * self.azimuthal_roughness
+    }
+
+    // density of the azimuth difference phi of each lobe
+    fn azimuthal(&self, lobe: usize, phi: f32) -> f32 {
+        match lobe {
+            // a smooth cylinder reflects evenly over its width
+            0 => (phi / 2.0).cos() / 4.0,
+            1 => trimmed_logistic(wrap_angle(phi - PI), self.azimuthal_scale(), -PI, PI),
+            _ => 1.0 / (2.0 * PI),
+        }
+    }
+
+    // densities of each lobe, over the sphere of directions, of scattering in the direction
+    fn lobe_densities(&self, fiber: &Fiber, direction: &Vec3) -> [f32; 3] {
+        let direction = normalize(direction);
+        let sin_theta = dot(&direction, &fiber.axis).clamp(-1.0, 1.0);
+        let (theta, cos_theta) = (sin_theta.asin(), f32::sqrt(1.0 - sin_theta * sin_theta));
+        let phi = wrap_angle(
+            dot(&direction, &fiber.bitangent).atan2(dot(&direction, &fiber.normal)) - fiber.phi_in,
+        );
+        let longitudinal = self.longitudinal(fiber.theta_in);
+        let mut densities = [0.0; 3];
+        for (lobe, density) in densities.iter_mut().enumerate() {
+            let (center, scale) = longitudinal[lobe];
+            let m = trimmed_logistic(theta - center, scale, -PI / 2.0 - center, PI / 2.0 - center);
+            // over solid angle, d(omega) = cos(theta) d(theta) d(phi)
+            *density = m / cos_theta.max(1e-4) * self.azimuthal(lobe, phi);
+        }
+        densities
+    }
+}
+
+impl Material for Hair {
+    // returns None if no ray is scattered
+    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<Ray> {
+        let fiber = self.fiber(ray_in, hit_record);
+        let choice = rand::random::<f32>();
+        let lobe = if choice < fiber.probabilities[0] {
+            0
+        } else if choice < fiber.probabilities[0] + fiber.probabilities[1] {
+            1
+        } else {
+            2
+        };
+
+        let (center, scale) = self.longitudinal(fiber.theta_in)[lobe];
+        let theta = center + sample_trimmed_logistic(scale, -PI / 2.0 - center, PI / 2.0 - center);
+        let phi = fiber.phi_in
+            + match lobe {
+                0 => -2.0 * (2.0 * rand::random::<f32>() - 1.0).asin(),
+                1 => PI + sample_trimmed_logistic(self.azimuthal_scale(), -PI, PI),
+                _ => 2.0 * PI * rand::random::<f32>(),
+            };
+        let direction = theta.sin() * fiber.axis
+            + theta.cos() * (phi.cos() * fiber.normal + phi.sin() * fiber.bitangent);
+        Some(Ray::with_time(hit_record.point, direction, ray_in.time))
+    }
+    // only an estimate of the color of the fiber, scattered rays are weighted by eval and
+    // scattering_pdf
+    fn albedo(&self, hit_record: &HitRecord) -> Vec3 {
+        hit_record.tint(
+            self.color
+                .value(hit_record.u, hit_record.v, &hit_record.point),
+        )
+    }
+
+    fn weight(&self, ray_in: &Ray, hit_record: &HitRecord, scattered: &Ray) -> Vec3 {
+        let pdf = self.scattering_pdf(ray_in, hit_record, &scattered.direction);
+        if pdf <= 0.0 {
+            return Vec3::new(0.0, 0.0, 0.0);
+        }
+        self.eval(ray_in, hit_record, &scattered.direction) / pdf
+    }
+
+    fn is_specular(&self) -> bool {
+        false
+    }
+    // mixture of the densities of the lobes
+    fn scattering_pdf(&self, ray_in: &Ray, hit_record: &HitRecord, direction: &Vec3) -> f32 {
+        let fiber = self.fiber(ray_in, hit_record);
+        let densities = self.lobe_densities(&fiber, direction);
+        (0..3).map(|p| fiber.probabilities[p] * densities[p]).sum()
+    }
+    // the lobes are normalized, the cosine term is part of them
+    fn eval(&self, ray_in: &Ray, hit_record: &HitRecord, direction: &Vec3) -> Vec3 {
+        let fiber = self.fiber(ray_in, hit_record);
+        let densities = self.lobe_densities(&fiber, direction);
+        (0..3).fold(Vec3::new(0.0, 0.0, 0.0), |sum, p| {
+            sum + fiber.attenuations[p] * densities[p]
+        })
+    }
+}
+
+// brings an angle into [-pi, pi]
+fn wrap_angle(angle: f32) -> f32 {
+    let wrapped = (angle + PI).rem_euclid(2.0 * PI) - PI;
+    wrapped.clamp(-PI, PI)
+}
+
+// density of the logistic distribution of the given scale restricted to [low, high]
+fn trimmed_logistic(x: f32, scale: f32, low: f32, high: f32) -> f32 {
+    if x < low || x > high {
+        return 0.0;
+    }
+    let e = (-x.abs() / scale).exp();
+    let density = e / (scale * (1.0 + e) * (1.0 + e));
+    let cumulative = |x: f32| 1.0 / (1.0 + (-x / scale).exp());
+    density / (cumulative(high) - cumulative(low))
+}
+
+// inverts the cumulative distribution of the trimmed logistic distribution
+fn sample_trimmed_logistic(scale: f32, low: f32, high: f32) -> f32 {
+    let cumulative = |x: f32| 1.0 / (1.0 + (-x / scale).exp());
+    let k = cumulative(low) + rand::random::<f32>() * (cumulative(high) - cumulative(low));
+    let x = -scale * (1.0 / k - 1.0).ln();
+    x.clamp(low, high)
+}
@@ -0,0 +1,78 @@
+use super::Material;
+use crate::collision::HitRecord;
+use crate::ray::Ray;
+use crate::texture::{Constant, Texture};
+use crate::utils::orthonormal_basis;
+use nalgebra_glm::{dot, normalize, Vec3};
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+// Anisotropic phase function of Henyey and Greenstein for participating media. The asymmetry g
+// goes from -1 (light scattered back) to 1 (light keeping its direction), 0 is isotropic. Clouds
+// and haze are strongly forward scattering, with g around 0.8.
+pub struct HenyeyGreenstein {
+    pub albedo: Arc<dyn Texture>,
+    pub g: f32,
+}
+
+impl HenyeyGreenstein {
+    pub fn new(albedo: Vec3, g: f32) -> Self {
+        HenyeyGreenstein::with_texture(Arc::new(Constant::new(albedo)), g)
+    }
+
+    pub fn with_texture(albedo: Arc<dyn Texture>, g: f32) -> Self {
+        // the pdf is a dirac at the limits
+        let g = g.clamp(-0.99, 0.99);
+        HenyeyGreenstein { albedo, g }
+    }
+
+    // density of the cosine of the angle between the incoming and outgoing directions, over
+    // the sphere of directions
+    fn phase(&self, cos_theta: f32) -> f32 {
+        let g = self.g;
+        let denominator = 1.0 + g * g - 2.0 * g * cos_theta;
+        (1.0 - g * g) / (4.0 * PI * denominator * f32::sqrt(denominator))
+    }
+
+    // inverts the cumulative distribution of the phase function
+    fn sample_cos_theta(&self) -> f32 {
+        let g = self.g;
+        let r = rand::random::<f32>();
+        if g.abs() < 1e-3 {
+            return 1.0 - 2.0 * r;
+        }
+        let s = (1.0 - g * g) / (1.0 - g + 2.0 * g * r);
+        ((1.0 + g * g - s * s) / (2.0 * g)).clamp(-1.0, 1.0)
+    }
+}
+
+impl Material for HenyeyGreenstein {
+    // returns None if no ray is scattered
+    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<Ray> {
+        let forward = normalize(&ray_in.direction);
+        let cos_theta = self.sample_cos_theta();
+        let sin_theta = f32::sqrt(f32::max(0.0, 1.0 - cos_theta * cos_theta));
+        let phi = 2.0 * PI * rand::random::<f32>();
+        let (u, v) = orthonormal_basis(&forward);
+        let direction = sin_theta * (f32::cos(phi) * u + f32::sin(phi) * v) + cos_theta * forward;
+        Some(Ray::with_time(hit_record.point, direction, ray_in.time))
+    }
+    // returns the albedo or attenuation of the surface at the hit point
+    fn albedo(&self, hit_record: &HitRecord) -> Vec3 {
+        self.albedo
+            .value(hit_record.u, hit_record.v, &hit_record.point)
+    }
+
+    fn is_specular(&self) -> bool {
+        false
+    }
+    // the angle is measured from the direction the light was going before scattering
+    #[allow(unused_variables)]
+    fn scattering_pdf(&self, ray_in: &Ray, hit_record: &HitRecord, direction: &Vec3) -> f32 {
+        self.phase(dot(&normalize(&ray_in.direction), &normalize(direction)))
+    }
+
+    fn eval(&self, ray_in: &Ray, hit_record: &HitRecord, direction: &Vec3) -> Vec3 {
+        self.albedo(hit_record) * self.scattering_pdf(ray_in, hit_record, direction)
+    }
+}
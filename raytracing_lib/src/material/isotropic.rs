@@ -0,0 +1,61 @@
+use super::Material;
+use crate::collision::HitRecord;
+use crate::ray::Ray;
+use crate::texture::{Constant, Texture};
+use crate::utils::random_unit_vector;
+use nalgebra_glm::Vec3;
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+// Phase function of a participating medium scattering light equally in all directions, to be
+// used with volumes like ConstantMedium. The albedo is the fraction of light scattered rather
+// than absorbed at each interaction.
+pub struct Isotropic {
+    pub albedo: Arc<dyn Texture>,
+}
+
+impl Isotropic {
+    pub fn new(albedo: Vec3) -> Self {
+        Isotropic::with_texture(Arc::new(Constant::new(albedo)))
+    }
+
+    pub fn with_texture(albedo: Arc<dyn Texture>) -> Self {
+        Isotropic { albedo }
+    }
+}
+
+impl Material for Isotropic {
+    // returns None if no ray is scattered
+    #[allow(unused_variables)]
+    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<Ray> {
+        Some(Ray::with_time(
+            hit_record.point,
+            random_unit_vector(),
+            ray_in.time,
+        ))
+    }
+    // returns the albedo or attenuation of the surface at the hit point
+    fn albedo(&self, hit_record: &HitRecord) -> Vec3 {
+        self.albedo
+            .value(hit_record.u, hit_record.v, &hit_record.point)
+    }
+
+    fn is_specular(&self) -> bool {
+        false
+    }
+    // scattered rays are uniform over the sphere, there is no surface to stay above
+    #[allow(unused_variables)]
+    fn scattering_pdf(&self, ray_in: &Ray, hit_record: &HitRecord, direction: &Vec3) -> f32 {
+        1.0 / (4.0 * PI)
+    }
+
+    fn eval(&self, ray_in: &Ray, hit_record: &HitRecord, direction: &Vec3) -> Vec3 {
+        self.albedo(hit_record) * self.scattering_pdf(ray_in, hit_record, direction)
+    }
+}
+
+impl Default for Isotropic {
+    fn default() -> Self {
+        Isotropic::new(Vec3::new(1.0, 1.0, 1.0))
+    }
+}
@@ -1,18 +1,35 @@
-use super::Material;
+use super::{Material, NormalPerturbation};
 use crate::collision::HitRecord;
 use crate::ray::Ray;
+use crate::texture::{Constant, Texture};
 use crate::utils::random_in_unit_sphere;
 use nalgebra_glm::{dot, normalize, reflect_vec, Vec3};
+use std::f32::consts::PI;
+use std::sync::Arc;
 
 pub struct Metal {
-    pub albedo: Vec3,
+    pub albedo: Arc<dyn Texture>,
     pub fuziness: f32,
+    pub normal_perturbation: Option<NormalPerturbation>,
 }
 
 impl Metal {
     pub fn new(albedo: Vec3, fuziness: f32) -> Self {
+        Metal::with_texture(Arc::new(Constant::new(albedo)), fuziness)
+    }
+
+    pub fn with_texture(albedo: Arc<dyn Texture>, fuziness: f32) -> Self {
         let fuziness = fuziness.clamp(0.0, 1.0);
-        Metal { albedo, fuziness }
+        Metal {
+            albedo,
+            fuziness,
+            normal_perturbation: None,
+        }
+    }
+
+    pub fn normal_perturbation(mut self, normal_perturbation: NormalPerturbation) -> Self {
+        self.normal_perturbation = Some(normal_perturbation);
+        self
     }
 }
 
@@ -21,23 +38,62 @@ impl Material for Metal {
     fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<Ray> {
         let reflected = normalize(&reflect_vec(&ray_in.direction, &hit_record.normal));
         let reflected = reflected + self.fuziness * random_in_unit_sphere();
-        if dot(&reflected, &hit_record.normal) > 0.0 {
-            Some(Ray::new(hit_record.point, reflected))
+        // reflecting off the shading normal can still go through the actual surface
+        if dot(&reflected, &hit_record.geometric_normal) > 0.0 {
+            Some(Ray::with_time(hit_record.point, reflected, ray_in.time))
         } else {
             None
         }
     }
-    // returns the albedo or attenuation of the surface
-    fn albedo(&self) -> &Vec3 {
-        &self.albedo
+    // returns the albedo or attenuation of the surface at the hit point
+    fn albedo(&self, hit_record: &HitRecord) -> Vec3 {
+        hit_record.tint(
+            self.albedo
+                .value(hit_record.u, hit_record.v, &hit_record.point),
+        )
+    }
+
+    fn perturb_normal(&self, hit_record: &mut HitRecord) {
+        if let Some(perturbation) = &self.normal_perturbation {
+            perturbation.apply(hit_record);
+        }
+    }
+
+    fn is_specular(&self) -> bool {
+        self.fuziness == 0.0
+    }
+    // scattered rays point toward a point uniformly distributed in the ball of radius fuziness
+    // centered on the tip of the reflected direction, the density of a direction is then the
+    // volume of the ball seen along it: the integral of t² over the chord [t_near, t_far]
+    fn scattering_pdf(&self, ray_in: &Ray, hit_record: &HitRecord, direction: &Vec3) -> f32 {
+        if self.fuziness == 0.0 {
+            return 0.0;
+        }
+        let reflected = normalize(&reflect_vec(&ray_in.direction, &hit_record.normal));
+        let b = dot(&normalize(direction), &reflected);
+        let discriminant = b * b - 1.0 + self.fuziness * self.fuziness;
+        if discriminant <= 0.0 {
+            return 0.0;
+        }
+        let root = f32::sqrt(discriminant);
+        let t_far = b + root;
+        if t_far <= 0.0 {
+            return 0.0;
+        }
+        let t_near = f32::max(b - root, 0.0);
+        (t_far.powi(3) - t_near.powi(3)) / (4.0 * PI * self.fuziness.powi(3))
+    }
+    // directions going below the surface are absorbed by scatter
+    fn eval(&self, ray_in: &Ray, hit_record: &HitRecord, direction: &Vec3) -> Vec3 {
+        if dot(direction, &hit_record.geometric_normal) <= 0.0 {
+            return Vec3::new(0.0, 0.0, 0.0);
+        }
+        self.albedo(hit_record) * self.scattering_pdf(ray_in, hit_record, direction)
     }
 }
 
 impl Default for Metal {
     fn default() -> Self {
-        Metal {
-            albedo: Vec3::new(0.5, 0.5, 0.5),
-            fuziness: 0.0,
-        }
+        Metal::new(Vec3::new(0.5, 0.5, 0.5), 0.0)
     }
 }
@@ -0,0 +1,217 @@
+use super::{Material, NormalPerturbation};
+use crate::collision::HitRecord;
+use crate::ray::Ray;
+use crate::texture::{Constant, Texture};
+use crate::utils::random_unit_vector;
+use nalgebra_glm::{dot, normalize, Vec3};
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+// below this roughness the microfacet distribution is too sharp to be evaluated in single
+// precision, smoother surfaces are rendered with it
+const MIN_ROUGHNESS: f32 = 0.03;
+
+// The physically based material of glTF: a blend, driven by metallic, between a conductor
+// reflecting the base color and a plastic-like dielectric made of a diffuse base color under a
+// specular coating. Both use the GGX microfacet distribution with the given roughness and
+// Schlick's approximation of the Fresnel factor (glTF 2.0 specification, appendix B).
+pub struct MetallicRoughness {
+    pub base_color: Arc<dyn Texture>,
+    pub metallic: f32,
+    pub roughness: f32,
+    // scales roughness by its green channel and metallic by its blue one
+    pub metallic_roughness: Option<Arc<dyn Texture>>,
+    pub emissive: Option<Arc<dyn Texture>>,
+    pub normal_perturbation: Option<NormalPerturbation>,
+}
+
+// parameters of the material at a hit point
+struct Surface {
+    base_color: Vec3,
+    metallic: f32,
+    // width of the microfacet distribution, the square of the roughness
+    alpha: f32,
+}
+
+impl MetallicRoughness {
+    pub fn new(base_color: Vec3, metallic: f32, roughness: f32) -> Self {
+        MetallicRoughness::with_texture(Arc::new(Constant::new(base_color)), metallic, roughness)
+    }
+
+    pub fn with_texture(base_color: Arc<dyn Texture>, metallic: f32, roughness: f32) -> Self {
+        MetallicRoughness {
+            base_color,
+            metallic: metallic.clamp(0.0, 1.0),
+            roughness: roughness.clamp(0.0, 1.0),
+            metallic_roughness: None,
+            emissive: None,
+            normal_perturbation: None,
+        }
+    }
+
+    pub fn metallic_roughness(mut self, texture: Arc<dyn Texture>) -> Self {
+        self.metallic_roughness = Some(texture);
+        self
+    }
+
+    pub fn emissive(mut self, emissive: Arc<dyn Texture>) -> Self {
+        self.emissive = Some(emissive);
+        self
+    }
+
+    pub fn normal_perturbation(mut self, normal_perturbation: NormalPerturbation) -> Self {
+        self.normal_perturbation = Some(normal_perturbation);
+        self
+    }
+
+    fn surface(&self, hit_record: &HitRecord) -> Surface {
+        let (u, v, point) = (hit_record.u, hit_record.v, &hit_record.point);
+        let (mut metallic, mut roughness) = (self.metallic, self.roughness);
+        if let Some(texture) = &self.metallic_roughness {
+            let value = texture.value(u, v, point);
+            roughness *= value.y;
+            metallic *= value.z;
+        }
+        let roughness = roughness.clamp(MIN_ROUGHNESS, 1.0);
+        Surface {
+            base_color: hit_record.tint(self.base_color.value(u, v, point)),
+            metallic: metallic.clamp(0.0, 1.0),
+            alpha: roughness * roughness,
+        }
+    }
+}
+
+// probability of sampling the specular lobe rather than the diffuse one, metals have no diffuse
+// lobe
+fn specular_probability(surface: &Surface) -> f32 {
+    0.25 + 0.75 * surface.metallic
+}
+
+// GGX distribution of the microfacet normals, n_h is the cosine between the normal and a
+// microfacet normal
+fn distribution(n_h: f32, alpha: f32) -> f32 {
+    let alpha2 = alpha * alpha;
+    let d = n_h * n_h * (alpha2 - 1.0) + 1.0;
+    alpha2 / (PI * d * d)
+}
+
+// height correlated Smith masking-shadowing term divided by 4 n_l n_v
+fn visibility(n_l: f32, n_v: f32, alpha: f32) -> f32 {
+    let alpha2 = alpha * alpha;
+    let v = n_l * f32::sqrt(n_v * n_v * (1.0 - alpha2) + alpha2);
+    let l = n_v * f32::sqrt(n_l * n_l * (1.0 - alpha2) + alpha2);
+    0.5 / (v + l)
+}
+
+impl Material for MetallicRoughness {
+    // returns None if no ray is scattered
+    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<Ray> {
+        let surface = self.surface(hit_record);
+        let normal = hit_record.normal;
+        let direction = if rand::random::<f32>() < specular_probability(&surface) {
+            // reflection on a microfacet normal sampled from the distribution
+            let view = -normalize(&ray_in.direction);
+            let (r1, r2) = (rand::random::<f32>(), rand::random::<f32>());
+            let alpha2 = surface.alpha * surface.alpha;
+            let cos_theta = f32::sqrt((1.0 - r1) / (1.0 + (alpha2 - 1.0) * r1));
+            let sin_theta = f32::sqrt((1.0 - cos_theta * cos_theta).max(0.0));
+            let phi = 2.0 * PI * r2;
+            let half = sin_theta * phi.cos() * hit_record.tangent
+                + sin_theta * phi.sin() * hit_record.bitangent
+                + cos_theta * normal;
+            2.0 * dot(&view, &half) * half - view
+        } else {
+            normal + random_unit_vector()
+        };
+        // directions under the shading normal have no reflectance
+        if dot(&direction, &hit_record.geometric_normal) <= 0.0 || dot(&direction, &normal) <= 0.0 {
+            return None;
+        }
+        Some(Ray::with_time(hit_record.point, direction, ray_in.time))
+    }
+    // only an estimate of the reflected color, scattered rays are weighted by eval and
+    // scattering_pdf
+    fn albedo(&self, hit_record: &HitRecord) -> Vec3 {
+        self.surface(hit_record).base_color
+    }
+
+    fn weight(&self, ray_in: &Ray, hit_record: &HitRecord, scattered: &Ray) -> Vec3 {
+        let pdf = self.scattering_pdf(ray_in, hit_record, &scattered.direction);
+        if pdf <= 0.0 {
+            return Vec3::new(0.0, 0.0, 0.0);
+        }
+        self.eval(ray_in, hit_record, &scattered.direction) / pdf
+    }
+
+    fn perturb_normal(&self, hit_record: &mut HitRecord) {
+        if let Some(perturbation) = &self.normal_perturbation {
+            perturbation.apply(hit_record);
+        }
+    }
+
+    fn emitted(&self, hit_record: &HitRecord) -> Vec3 {
+        match &self.emissive {
+            Some(emissive) => emissive.value(hit_record.u, hit_record.v, &hit_record.point),
+            None => Vec3::new(0.0, 0.0, 0.0),
+        }
+    }
+
+    fn is_specular(&self) -> bool {
+        false
+    }
+    // mixture of the densities of both lobes
+    fn scattering_pdf(&self, ray_in: &Ray, hit_record: &HitRecord, direction: &Vec3) -> f32 {
+        let surface = self.surface(hit_record);
+        let normal = hit_record.normal;
+        let (view, light) = (-normalize(&ray_in.direction), normalize(direction));
+        let n_l = dot(&normal, &light);
+        if n_l <= 0.0 {
+            return 0.0;
+        }
+        let half = normalize(&(view + light));
+        let v_h = dot(&view, &half);
+        let specular = if v_h > 0.0 {
+            let n_h = dot(&normal, &half).max(0.0);
+            distribution(n_h, surface.alpha) * n_h / (4.0 * v_h)
+        } else {
+            0.0
+        };
+        let p = specular_probability(&surface);
+        p * specular + (1.0 - p) * n_l / PI
+    }
+
+    fn eval(&self, ray_in: &Ray, hit_record: &HitRecord, direction: &Vec3) -> Vec3 {
+        let black = Vec3::new(0.0, 0.0, 0.0);
+        if dot(direction, &hit_record.geometric_normal) <= 0.0 {
+            return black;
+        }
+        let surface = self.surface(hit_record);
+        let normal = hit_record.normal;
+        let (view, light) = (-normalize(&ray_in.direction), normalize(direction));
+        let (n_l, n_v) = (dot(&normal, &light), dot(&normal, &view));
+        if n_l <= 0.0 || n_v <= 0.0 {
+            return black;
+        }
+        let half = normalize(&(view + light));
+        let n_h = dot(&normal, &half).max(0.0);
+        let v_h = dot(&view, &half).max(0.0);
+
+        let specular = distribution(n_h, surface.alpha) * visibility(n_l, n_v, surface.alpha);
+        let schlick = (1.0 - v_h).powi(5);
+        // the coating of dielectrics reflects 4% of the light at normal incidence (IOR 1.5),
+        // metals their base color
+        let coating_fresnel = 0.04 + 0.96 * schlick;
+        let metal_fresnel =
+            surface.base_color + (Vec3::new(1.0, 1.0, 1.0) - surface.base_color) * schlick;
+        let dielectric = surface.base_color * (1.0 - coating_fresnel) / PI
+            + Vec3::new(specular, specular, specular) * coating_fresnel;
+        let metal = metal_fresnel * specular;
+        (dielectric * (1.0 - surface.metallic) + metal * surface.metallic) * n_l
+    }
+}
+
+impl Default for MetallicRoughness {
+    fn default() -> Self {
+        MetallicRoughness::new(Vec3::new(1.0, 1.0, 1.0), 1.0, 1.0)
+    }
+}
@@ -4,15 +4,60 @@ use nalgebra_glm::Vec3;
 
 mod dielectric;
 mod diffuse;
+mod diffuse_light;
+mod hair;
+mod henyey_greenstein;
+mod isotropic;
 mod metal;
+mod metallic_roughness;
+mod normal_map;
 
 pub use dielectric::Dielectric;
 pub use diffuse::Diffuse;
+pub use diffuse_light::DiffuseLight;
+pub use hair::Hair;
+pub use henyey_greenstein::HenyeyGreenstein;
+pub use isotropic::Isotropic;
 pub use metal::Metal;
+pub use metallic_roughness::MetallicRoughness;
+pub use normal_map::NormalPerturbation;
 
 pub trait Material: Send + Sync {
     // returns None if no ray is scattered
     fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<Ray>;
-    // returns the albedo or attenuation of the surface
-    fn albedo(&self) -> &Vec3;
+    // returns the albedo or attenuation of the surface at the hit point
+    fn albedo(&self, hit_record: &HitRecord) -> Vec3;
+    // returns the factor the light coming along a ray returned by scatter is multiplied by,
+    // eval / scattering_pdf for non specular materials. It is the albedo unless the ratio depends
+    // on the scattered direction, like for microfacet materials
+    #[allow(unused_variables)]
+    fn weight(&self, ray_in: &Ray, hit_record: &HitRecord, scattered: &Ray) -> Vec3 {
+        self.albedo(hit_record)
+    }
+    // lets the material change the shading normal of the hit point (normal or bump mapping),
+    // called once on each hit before any other method
+    #[allow(unused_variables)]
+    fn perturb_normal(&self, hit_record: &mut HitRecord) {}
+    // returns the light emitted by the surface at the hit point, black for non emissive materials
+    #[allow(unused_variables)]
+    fn emitted(&self, hit_record: &HitRecord) -> Vec3 {
+        Vec3::new(0.0, 0.0, 0.0)
+    }
+    // returns true if scatter only ever picks one direction (perfect mirrors and glass), such
+    // materials cannot be evaluated for arbitrary directions so lights are not sampled explicitly
+    fn is_specular(&self) -> bool {
+        true
+    }
+    // returns the probability density, over the sphere of directions, of scatter returning the
+    // given direction
+    #[allow(unused_variables)]
+    fn scattering_pdf(&self, ray_in: &Ray, hit_record: &HitRecord, direction: &Vec3) -> f32 {
+        0.0
+    }
+    // returns the fraction of the light coming from the given direction that is scattered along
+    // ray_in, cosine term included, so that weight == eval / scattering_pdf for scattered rays
+    #[allow(unused_variables)]
+    fn eval(&self, ray_in: &Ray, hit_record: &HitRecord, direction: &Vec3) -> Vec3 {
+        Vec3::new(0.0, 0.0, 0.0)
+    }
 }
@@ -0,0 +1,70 @@
+use crate::collision::HitRecord;
+use crate::texture::Texture;
+use nalgebra_glm::{cross, dot, length2, Vec3};
+use std::sync::Arc;
+
+// step used to differentiate bump maps, in uv units
+const BUMP_DELTA: f32 = 1e-3;
+
+// ways for a material to change the shading normal of a surface to fake small details
+pub enum NormalPerturbation {
+    // tangent space normal map, colors are (n + 1) / 2 with red along the tangent, green along
+    // the bitangent and blue along the normal. The texture must hold linear values
+    NormalMap(Arc<dyn Texture>),
+    // height map (mean of the color channels) offsetting the surface along its normal by strength
+    // world units per unit of height. Surfaces without uv derivatives are offset as if u and v
+    // were distances along their tangent and bitangent
+    BumpMap {
+        height: Arc<dyn Texture>,
+        strength: f32,
+    },
+}
+
+impl NormalPerturbation {
+    pub fn apply(&self, hit_record: &mut HitRecord) {
+        let (u, v, point) = (hit_record.u, hit_record.v, hit_record.point);
+        let (normal, tangent, bitangent) =
+            (hit_record.normal, hit_record.tangent, hit_record.bitangent);
+        let perturbed = match self {
+            NormalPerturbation::NormalMap(map) => {
+                let n = map.value(u, v, &point) * 2.0 - Vec3::new(1.0, 1.0, 1.0);
+                n.x * tangent + n.y * bitangent + n.z * normal
+            }
+            NormalPerturbation::BumpMap { height, strength } => {
+                let height_at = |u: f32, v: f32, point: &Vec3| {
+                    let c = height.value(u, v, point);
+                    (c.x + c.y + c.z) / 3.0
+                };
+                // derivatives in the plane of the shading normal, which may not be the one of
+                // the surface
+                let in_plane = |d: &Vec3| d - dot(d, &normal) * normal;
+                let (dpdu, dpdv) = (in_plane(&hit_record.dpdu), in_plane(&hit_record.dpdv));
+                let (dpdu, dpdv) = if length2(&cross(&dpdu, &dpdv)) > 0.0 {
+                    (dpdu, dpdv)
+                } else {
+                    (tangent, bitangent)
+                };
+                // moving along u and v also moves the point on the surface, so that solid
+                // textures get differentiated as well
+                let h = height_at(u, v, &point);
+                let dh_du =
+                    (height_at(u + BUMP_DELTA, v, &(point + BUMP_DELTA * dpdu)) - h) / BUMP_DELTA;
+                let dh_dv =
+                    (height_at(u, v + BUMP_DELTA, &(point + BUMP_DELTA * dpdv)) - h) / BUMP_DELTA;
+                // normal of the offset surface, whose derivatives also move along the normal
+                let bumped = cross(
+                    &(dpdu + *strength * dh_du * normal),
+                    &(dpdv + *strength * dh_dv * normal),
+                );
+                if dot(&bumped, &normal) < 0.0 {
+                    -bumped
+                } else {
+                    bumped
+                }
+            }
+        };
+        if length2(&perturbed) > 0.0 {
+            hit_record.set_shading_normal(&perturbed);
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use crate::{aabb::AABB, ray::Ray, utils::orthonormal_basis};
+use nalgebra_glm::{dot, normalize, Vec3};
+use std::f32::consts::PI;
+
+// Local frame of the shapes built around an axis (disks, cylinders, cones and tori). Local
+// coordinates are x along tangent, y along the axis and z along bitangent, from origin.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct AxisFrame {
+    pub origin: Vec3,
+    pub axis: Vec3,
+    tangent: Vec3,
+    bitangent: Vec3,
+}
+
+impl AxisFrame {
+    pub fn new(origin: Vec3, axis: Vec3) -> Self {
+        let axis = normalize(&axis);
+        let (tangent, bitangent) = orthonormal_basis(&axis);
+        AxisFrame {
+            origin,
+            axis,
+            tangent,
+            bitangent,
+        }
+    }
+
+    pub fn direction_to_local(&self, direction: &Vec3) -> Vec3 {
+        Vec3::new(
+            dot(direction, &self.tangent),
+            dot(direction, &self.axis),
+            dot(direction, &self.bitangent),
+        )
+    }
+
+    pub fn direction_to_world(&self, local: &Vec3) -> Vec3 {
+        local.x * self.tangent + local.y * self.axis + local.z * self.bitangent
+    }
+
+    // origin and direction of the ray in local coordinates, the ray parameter is unchanged
+    pub fn ray_to_local(&self, r: &Ray) -> (Vec3, Vec3) {
+        (
+            self.direction_to_local(&(r.origin - self.origin)),
+            self.direction_to_local(&r.direction),
+        )
+    }
+
+    // angle of a local point around the axis, from 0 to 1, and the local direction in which it
+    // increases
+    pub fn azimuth(local: &Vec3) -> (f32, Vec3) {
+        let phi = f32::atan2(local.z, local.x);
+        let u = if phi < 0.0 {
+            (phi + 2.0 * PI) / (2.0 * PI)
+        } else {
+            phi / (2.0 * PI)
+        };
+        (u, Vec3::new(-local.z, 0.0, local.x))
+    }
+
+    // bounding box of the circle of the given radius around the axis, at the given height
+    pub fn circle_bounds(&self, height: f32, radius: f32) -> AABB {
+        let center = self.origin + height * self.axis;
+        let extent =
+            Vec3::from_fn(|a, _| radius * f32::sqrt((1.0 - self.axis[a].powi(2)).max(0.0)));
+        AABB {
+            min: center - extent,
+            max: center + extent,
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use super::{axis_frame::AxisFrame, disk::hit_cap, Position};
+use crate::{
+    aabb::AABB,
+    collision::{HitRecord, Hittable},
+};
+use crate::{material::Material, ray::Ray};
+use nalgebra_glm::{length, length2, normalize, Vec3};
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+// A closed cone going from a disk around base to its apex, or a truncated one with a smaller disk
+// at the top. The side has u going around the axis and v going from the base to the top, the caps
+// have polar texture coordinates like disks.
+pub struct Cone {
+    frame: AxisFrame,
+    height: f32,
+    base_radius: f32,
+    top_radius: f32,
+    center: Vec3,
+
+    material: Arc<Box<dyn Material>>,
+}
+
+impl std::fmt::Debug for Cone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format!(
+            "base:{:?} axis:{:?} h:{:?} R:{:?} r:{:?}",
+            self.frame.origin, self.frame.axis, self.height, self.base_radius, self.top_radius
+        ))
+    }
+}
+
+impl Cone {
+    pub fn new(base: Vec3, apex: Vec3, radius: f32, material: Arc<Box<dyn Material>>) -> Self {
+        Cone::truncated(base, apex, radius, 0.0, material)
+    }
+
+    pub fn truncated(
+        base: Vec3,
+        top: Vec3,
+        base_radius: f32,
+        top_radius: f32,
+        material: Arc<Box<dyn Material>>,
+    ) -> Self {
+        Cone {
+            frame: AxisFrame::new(base, top - base),
+            height: length(&(top - base)),
+            base_radius,
+            top_radius,
+            center: (base + top) / 2.0,
+            material,
+        }
+    }
+}
+
+impl Hittable for Cone {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        hit_frustum(
+            &self.frame,
+            self.height,
+            self.base_radius,
+            self.top_radius,
+            &self.material,
+            r,
+            t_min,
+            t_max,
+        )
+    }
+
+    fn bounding_box(&self, _t0: f32, _t1: f32) -> Option<AABB> {
+        Some(frustum_bounds(
+            &self.frame,
+            self.height,
+            self.base_radius,
+            self.top_radius,
+        ))
+    }
+}
+
+impl Position for Cone {
+    fn position(&self) -> &Vec3 {
+        &self.center
+    }
+}
+
+// intersection with the closed truncated cone of the given height along the axis of frame, with
+// the given radii at its bottom and top, shared with cylinders
+#[allow(clippy::too_many_arguments)]
+pub(super) fn hit_frustum(
+    frame: &AxisFrame,
+    height: f32,
+    base_radius: f32,
+    top_radius: f32,
+    material: &Arc<Box<dyn Material>>,
+    r: &Ray,
+    t_min: f32,
+    t_max: f32,
+) -> Option<HitRecord> {
+    let (origin, direction) = frame.ray_to_local(r);
+    // the radius grows by slope per unit of height
+    let slope = (top_radius - base_radius) / height;
+    let radius_at_origin = base_radius + slope * origin.y;
+
+    let a = direction.x * direction.x + direction.z * direction.z
+        - slope * slope * direction.y * direction.y;
+    let half_b =
+        origin.x * direction.x + origin.z * direction.z - slope * radius_at_origin * direction.y;
+    let c = origin.x * origin.x + origin.z * origin.z - radius_at_origin * radius_at_origin;
+    let roots = if a.abs() < 1e-12 {
+        // the ray is parallel to the side of the cone, it crosses it once
+        if half_b == 0.0 {
+            [f32::NAN, f32::NAN]
+        } else {
+            [-c / (2.0 * half_b), f32::NAN]
+        }
+    } else {
+        let discriminant = half_b * half_b - a * c;
+        if discriminant < 0.0 {
+            [f32::NAN, f32::NAN]
+        } else {
+            let root = f32::sqrt(discriminant);
+            let (t0, t1) = ((-half_b - root) / a, (-half_b + root) / a);
+            [t0.min(t1), t0.max(t1)]
+        }
+    };
+
+    // the side is only the part of the quadric between the caps, the other nappe of the cone is
+    // cut out
+    let mut closest = None;
+    let mut t_max = t_max;
+    if let Some(&t) = roots.iter().find(|&&t| {
+        t < t_max && t > t_min && (0.0..=height).contains(&(origin.y + t * direction.y))
+    }) {
+        let local = origin + t * direction;
+        let radius = base_radius + slope * local.y;
+        let local_normal = Vec3::new(local.x, -slope * radius, local.z);
+        if length2(&local_normal) > 0.0 {
+            let outward_normal = frame.direction_to_world(&normalize(&local_normal));
+            let (u, tangent) = AxisFrame::azimuth(&local);
+            let dpdu = frame.direction_to_world(&(2.0 * PI * tangent));
+            // a point of the side moves out with the radius when going up
+            let dpdv = if radius > 0.0 {
+                let outward = slope / radius;
+                frame.direction_to_world(&Vec3::new(outward * local.x, 1.0, outward * local.z))
+                    * height
+            } else {
+                Vec3::new(0.0, 0.0, 0.0)
+            };
+            closest = Some(
+                HitRecord::new(
+                    r,
+                    t,
+                    &outward_normal,
+                    u,
+                    local.y / height,
+                    Arc::clone(material),
+                )
+                .with_shading_frame(&outward_normal, &dpdu)
+                .with_uv_derivatives(dpdu, dpdv),
+            );
+            t_max = t;
+        }
+    }
+
+    for &(cap_height, radius, sign) in &[(0.0, base_radius, -1.0), (height, top_radius, 1.0)] {
+        if radius <= 0.0 {
+            continue;
+        }
+        let outward_normal = sign * frame.axis;
+        if let Some(record) = hit_cap(
+            frame,
+            cap_height,
+            radius,
+            &outward_normal,
+            material,
+            r,
+            &origin,
+            &direction,
+            t_min,
+            t_max,
+        ) {
+            t_max = record.t;
+            closest = Some(record);
+        }
+    }
+    closest
+}
+
+pub(super) fn frustum_bounds(
+    frame: &AxisFrame,
+    height: f32,
+    base_radius: f32,
+    top_radius: f32,
+) -> AABB {
+    AABB::surrounding_box(
+        &frame.circle_bounds(0.0, base_radius),
+        &frame.circle_bounds(height, top_radius),
+    )
+}
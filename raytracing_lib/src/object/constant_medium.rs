@@ -0,0 +1,76 @@
+use crate::{
+    aabb::AABB,
+    collision::{HitRecord, Hittable},
+};
+use crate::{material::Material, ray::Ray};
+use nalgebra_glm::{length, normalize};
+use std::sync::Arc;
+
+// A participating medium (fog, smoke) of uniform density filling a closed convex boundary.
+// Rays going through it are hit at a random distance following the exponential distribution of
+// free paths, and the phase function material (see Isotropic) tells where they go next. Rays can
+// start inside the volume, like the ones scattered by the medium itself.
+pub struct ConstantMedium {
+    boundary: Arc<dyn Hittable>,
+    // probability of an interaction per unit of distance
+    density: f32,
+
+    phase_function: Arc<Box<dyn Material>>,
+}
+
+impl std::fmt::Debug for ConstantMedium {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format!("density:{:?}", self.density))
+    }
+}
+
+impl ConstantMedium {
+    pub fn new(
+        boundary: Arc<dyn Hittable>,
+        density: f32,
+        phase_function: Arc<Box<dyn Material>>,
+    ) -> Self {
+        ConstantMedium {
+            boundary,
+            density,
+            phase_function,
+        }
+    }
+}
+
+impl Hittable for ConstantMedium {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        // the whole line is intersected to find where the ray enters the volume, even behind
+        // its origin when it starts inside
+        let entry = self.boundary.hit(r, f32::NEG_INFINITY, f32::INFINITY)?;
+        let exit = self.boundary.hit(r, entry.t + 0.0001, f32::INFINITY)?;
+
+        let t_entry = f32::max(entry.t, t_min);
+        let t_exit = f32::min(exit.t, t_max);
+        if t_entry >= t_exit {
+            return None;
+        }
+
+        let ray_length = length(&r.direction);
+        let distance_inside = (t_exit - t_entry) * ray_length;
+        let hit_distance = -f32::ln(1.0 - rand::random::<f32>()) / self.density;
+        if hit_distance > distance_inside {
+            return None;
+        }
+
+        // there is no surface, the normal only needs to face the ray
+        let t = t_entry + hit_distance / ray_length;
+        Some(HitRecord::new(
+            r,
+            t,
+            &-normalize(&r.direction),
+            0.0,
+            0.0,
+            Arc::clone(&self.phase_function),
+        ))
+    }
+
+    fn bounding_box(&self, t0: f32, t1: f32) -> Option<AABB> {
+        self.boundary.bounding_box(t0, t1)
+    }
+}
@@ -0,0 +1,131 @@
+use crate::{
+    aabb::AABB,
+    collision::{HitRecord, Hittable},
+    ray::Ray,
+};
+use std::sync::Arc;
+
+// distance the search for the next crossing of an operand starts after the previous one
+const CROSSING_EPSILON: f32 = 1e-4;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CsgOperation {
+    Union,
+    Intersection,
+    // the left operand minus the right one
+    Difference,
+}
+
+impl CsgOperation {
+    fn contains(&self, in_left: bool, in_right: bool) -> bool {
+        match self {
+            CsgOperation::Union => in_left || in_right,
+            CsgOperation::Intersection => in_left && in_right,
+            CsgOperation::Difference => in_left && !in_right,
+        }
+    }
+}
+
+// Constructive solid geometry: a solid made of two closed objects combined by a boolean
+// operation. The crossings of both operands are walked along the ray, tracking whether it is
+// inside each of them, and the first one where the ray enters or leaves the result is returned.
+// Each surface keeps the material of its operand, so a sphere can be hollowed by a glass one.
+pub struct Csg {
+    operation: CsgOperation,
+    left: Arc<dyn Hittable>,
+    right: Arc<dyn Hittable>,
+}
+
+impl std::fmt::Debug for Csg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format!("operation:{:?}", self.operation))
+    }
+}
+
+impl Csg {
+    pub fn new(operation: CsgOperation, left: Arc<dyn Hittable>, right: Arc<dyn Hittable>) -> Self {
+        Csg {
+            operation,
+            left,
+            right,
+        }
+    }
+
+    pub fn union(left: Arc<dyn Hittable>, right: Arc<dyn Hittable>) -> Self {
+        Csg::new(CsgOperation::Union, left, right)
+    }
+
+    pub fn intersection(left: Arc<dyn Hittable>, right: Arc<dyn Hittable>) -> Self {
+        Csg::new(CsgOperation::Intersection, left, right)
+    }
+
+    pub fn difference(left: Arc<dyn Hittable>, right: Arc<dyn Hittable>) -> Self {
+        Csg::new(CsgOperation::Difference, left, right)
+    }
+}
+
+impl Hittable for Csg {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        let mut left = self.left.hit(r, t_min, f32::INFINITY);
+        let mut right = self.right.hit(r, t_min, f32::INFINITY);
+        // leaving an operand at its first crossing means the ray started inside it
+        let mut in_left = left.as_ref().is_some_and(|record| !record.front_face);
+        let mut in_right = right.as_ref().is_some_and(|record| !record.front_face);
+
+        loop {
+            let from_left = match (&left, &right) {
+                (None, None) => return None,
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (Some(left), Some(right)) => left.t <= right.t,
+            };
+            let mut record = if from_left {
+                left.take().unwrap()
+            } else {
+                right.take().unwrap()
+            };
+            if record.t >= t_max {
+                return None;
+            }
+
+            let was_inside = self.operation.contains(in_left, in_right);
+            if from_left {
+                in_left = record.front_face;
+            } else {
+                in_right = record.front_face;
+            }
+            if self.operation.contains(in_left, in_right) != was_inside {
+                // the inside of the subtracted operand is the outside of the result
+                if !from_left && self.operation == CsgOperation::Difference {
+                    record.front_face = !record.front_face;
+                }
+                return Some(record);
+            }
+
+            let next_t = record.t + CROSSING_EPSILON;
+            if from_left {
+                left = self.left.hit(r, next_t, f32::INFINITY);
+            } else {
+                right = self.right.hit(r, next_t, f32::INFINITY);
+            }
+        }
+    }
+
+    fn bounding_box(&self, t0: f32, t1: f32) -> Option<AABB> {
+        let left = self.left.bounding_box(t0, t1);
+        let right = self.right.bounding_box(t0, t1);
+        match self.operation {
+            CsgOperation::Union => Some(AABB::surrounding_box(&left?, &right?)),
+            CsgOperation::Intersection => match (left, right) {
+                (Some(left), Some(right)) => {
+                    let min = left.min.sup(&right.min);
+                    // disjoint operands give an empty box, clamped to a point
+                    let max = left.max.inf(&right.max).sup(&min);
+                    Some(AABB { min, max })
+                }
+                (left, right) => left.or(right),
+            },
+            CsgOperation::Difference => left,
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use super::Position;
+use crate::{
+    aabb::AABB,
+    collision::{HitRecord, Hittable},
+};
+use crate::{material::Material, ray::Ray};
+use nalgebra_glm::Vec3;
+use std::sync::Arc;
+
+// An axis aligned box, intersected with the slab method. Each face has texture coordinates going
+// from 0 to 1 along the two next axes (y and z for the x faces, z and x for the y faces, ...).
+pub struct Cuboid {
+    min: Vec3,
+    max: Vec3,
+    center: Vec3,
+
+    material: Arc<Box<dyn Material>>,
+}
+
+impl std::fmt::Debug for Cuboid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format!("min:{:?} max:{:?}", self.min, self.max))
+    }
+}
+
+impl Cuboid {
+    // the corners can be given in any order
+    pub fn new(a: Vec3, b: Vec3, material: Arc<Box<dyn Material>>) -> Self {
+        let min = a.inf(&b);
+        let max = a.sup(&b);
+        Cuboid {
+            min,
+            max,
+            center: (min + max) / 2.0,
+            material,
+        }
+    }
+}
+
+impl Hittable for Cuboid {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        // parametric interval of the ray inside the box, with the axes of the faces it goes
+        // through when entering and leaving
+        let (mut t_near, mut near_axis) = (f32::NEG_INFINITY, 0);
+        let (mut t_far, mut far_axis) = (f32::INFINITY, 0);
+        for a in 0..3 {
+            let inv_d = 1.0 / r.direction[a];
+            let mut t0 = (self.min[a] - r.origin[a]) * inv_d;
+            let mut t1 = (self.max[a] - r.origin[a]) * inv_d;
+            if inv_d < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            if t0 > t_near {
+                t_near = t0;
+                near_axis = a;
+            }
+            if t1 < t_far {
+                t_far = t1;
+                far_axis = a;
+            }
+        }
+        if t_near > t_far {
+            return None;
+        }
+
+        // the ray enters through the face looking toward it and leaves through the opposite one
+        let (t, axis, sign) = if t_near < t_max && t_near > t_min {
+            (t_near, near_axis, -r.direction[near_axis].signum())
+        } else if t_far < t_max && t_far > t_min {
+            (t_far, far_axis, r.direction[far_axis].signum())
+        } else {
+            return None;
+        };
+
+        let mut outward_normal = Vec3::new(0.0, 0.0, 0.0);
+        outward_normal[axis] = sign;
+        let point = r.at(t);
+        let (axis_u, axis_v) = ((axis + 1) % 3, (axis + 2) % 3);
+        let size = self.max - self.min;
+        let u = (point[axis_u] - self.min[axis_u]) / size[axis_u];
+        let v = (point[axis_v] - self.min[axis_v]) / size[axis_v];
+        let (mut dpdu, mut dpdv) = (Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.0));
+        dpdu[axis_u] = size[axis_u];
+        dpdv[axis_v] = size[axis_v];
+        Some(
+            HitRecord::new(r, t, &outward_normal, u, v, Arc::clone(&self.material))
+                .with_shading_frame(&outward_normal, &dpdu)
+                .with_uv_derivatives(dpdu, dpdv),
+        )
+    }
+
+    fn bounding_box(&self, _t0: f32, _t1: f32) -> Option<AABB> {
+        Some(AABB {
+            min: self.min,
+            max: self.max,
+        })
+    }
+}
+
+impl Position for Cuboid {
+    fn position(&self) -> &Vec3 {
+        &self.center
+    }
+}
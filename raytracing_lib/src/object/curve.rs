@@ -0,0 +1,364 @@
+use super::Position;
+use crate::utils::orthonormal_basis;
+use crate::{
+    aabb::{AABB, AABB_PADDING},
+    collision::{HitRecord, Hittable},
+};
+use crate::{material::Material, ray::Ray};
+use nalgebra_glm::{cross, dot, length, length2, normalize, Vec3};
+use std::sync::Arc;
+
+// the subdivision of a curve stops at this depth, enough for any reasonably bent segment
+const MAX_DEPTH: i32 = 10;
+
+// how the width of a curve is rendered, both are flat strips facing the ray
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CurveMode {
+    // the normal faces the ray, for blades of grass and thin strands
+    Ribbon,
+    // the normal goes around the curve across its width, shading it like a round tube
+    Cylinder,
+}
+
+// A cubic Bézier segment swept by a strip whose width goes linearly from widths[0] at its start
+// to widths[1] at its end. It is intersected by subdividing the curve until its pieces are almost
+// straight, as in pbrt (Physically Based Rendering, 3rd edition, section 3.7).
+// u goes along the curve and v across it, from 0 to 1. Hair materials find the direction of the
+// fiber in the tangent of the hit record, which follows the curve.
+pub struct Curve {
+    points: [Vec3; 4],
+    widths: [f32; 2],
+    mode: CurveMode,
+    // part of a strand covered by the segment, for its u coordinates
+    u_range: [f32; 2],
+    bounds: AABB,
+    center: Vec3,
+
+    material: Arc<Box<dyn Material>>,
+}
+
+impl std::fmt::Debug for Curve {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format!(
+            "points:{:?} widths:{:?} mode:{:?}",
+            self.points, self.widths, self.mode
+        ))
+    }
+}
+
+impl Curve {
+    pub fn bezier(
+        points: [Vec3; 4],
+        widths: [f32; 2],
+        mode: CurveMode,
+        material: Arc<Box<dyn Material>>,
+    ) -> Self {
+        let widths = [widths[0].max(0.0), widths[1].max(0.0)];
+        let bounds = curve_bounds(&points, &widths);
+        Curve {
+            points,
+            widths,
+            mode,
+            u_range: [0.0, 1.0],
+            bounds,
+            center: (bounds.min + bounds.max) / 2.0,
+            material,
+        }
+    }
+
+    // segment of a uniform cubic B-spline, which goes near the middle control points without
+    // passing through them
+    pub fn b_spline(
+        points: [Vec3; 4],
+        widths: [f32; 2],
+        mode: CurveMode,
+        material: Arc<Box<dyn Material>>,
+    ) -> Self {
+        let [p0, p1, p2, p3] = points;
+        Curve::bezier(
+            [
+                (p0 + 4.0 * p1 + p2) / 6.0,
+                (2.0 * p1 + p2) / 3.0,
+                (p1 + 2.0 * p2) / 3.0,
+                (p1 + 4.0 * p2 + p3) / 6.0,
+            ],
+            widths,
+            mode,
+            material,
+        )
+    }
+
+    // Splits a smooth strand going from the first to the last of the points, like a hair or a blade
+    // of grass, into B-spline segments. Its width goes linearly from root_width to tip_width and u
+    // from 0 at the root to 1 at the tip.
+    pub fn strand(
+        points: &[Vec3],
+        root_width: f32,
+        tip_width: f32,
+        mode: CurveMode,
+        material: Arc<Box<dyn Material>>,
+    ) -> Result<Vec<Self>, String> {
+        if points.len() < 2 {
+            return Err(format!(
+                "A strand needs at least 2 points, got {}",
+                points.len()
+            ));
+        }
+        // the B-spline ends at its first and last points if they are mirrored around them
+        let n = points.len();
+        let mut extended = Vec::with_capacity(n + 2);
+        extended.push(2.0 * points[0] - points[1]);
+        extended.extend_from_slice(points);
+        extended.push(2.0 * points[n - 1] - points[n - 2]);
+
+        let segments = n - 1;
+        let width = |s: usize| root_width + (tip_width - root_width) * s as f32 / segments as f32;
+        Ok(extended
+            .windows(4)
+            .enumerate()
+            .map(|(s, window)| {
+                let mut curve = Curve::b_spline(
+                    [window[0], window[1], window[2], window[3]],
+                    [width(s), width(s + 1)],
+                    mode,
+                    Arc::clone(&material),
+                );
+                curve.u_range = [s as f32 / segments as f32, (s + 1) as f32 / segments as f32];
+                curve
+            })
+            .collect())
+    }
+
+    fn width(&self, u: f32) -> f32 {
+        self.widths[0] + (self.widths[1] - self.widths[0]) * u
+    }
+
+    // Looks for the hit closest to the ray origin on the piece of the curve between u0 and u1,
+    // whose control points are given in the space of the ray, where it starts at the origin and
+    // goes along +z. Returns the distance along the ray and the u of the hit point.
+    fn hit_piece(
+        &self,
+        points: &[Vec3; 4],
+        (u0, u1): (f32, f32),
+        depth: i32,
+        (z_min, z_max): (f32, f32),
+    ) -> Option<(f32, f32)> {
+        if depth > 0 {
+            let [p0, p1, p2, p3] = *points;
+            let (a, b, c) = ((p0 + p1) / 2.0, (p1 + p2) / 2.0, (p2 + p3) / 2.0);
+            let (d, e) = ((a + b) / 2.0, (b + c) / 2.0);
+            let middle = (d + e) / 2.0;
+            let u_middle = (u0 + u1) / 2.0;
+            let halves = [
+                ([p0, a, d, middle], (u0, u_middle)),
+                ([middle, e, c, p3], (u_middle, u1)),
+            ];
+
+            let mut closest: Option<(f32, f32)> = None;
+            for (half, (v0, v1)) in halves.iter() {
+                let radius = self.width(*v0).max(self.width(*v1)) / 2.0;
+                let z_far = closest.map_or(z_max, |(z, _)| z);
+                if !piece_may_hit(half, radius, z_min, z_far) {
+                    continue;
+                }
+                if let Some(hit) = self.hit_piece(half, (*v0, *v1), depth - 1, (z_min, z_far)) {
+                    closest = Some(hit);
+                }
+            }
+            return closest;
+        }
+
+        // the piece is almost a straight line, the ray must go through the strip between the
+        // lines perpendicular to the curve at its ends
+        let [p0, p1, p2, p3] = *points;
+        if (p1.y - p0.y) * -p0.y + p0.x * (p0.x - p1.x) < 0.0
+            || (p2.y - p3.y) * -p3.y + p3.x * (p3.x - p2.x) < 0.0
+        {
+            return None;
+        }
+        // closest point of the line to the ray
+        let (dx, dy) = (p3.x - p0.x, p3.y - p0.y);
+        let denominator = dx * dx + dy * dy;
+        if denominator == 0.0 {
+            return None;
+        }
+        let w = ((-p0.x * dx - p0.y * dy) / denominator).clamp(0.0, 1.0);
+        let u = (u0 + (u1 - u0) * w).clamp(u0, u1);
+        let width = self.width(u);
+        let point = evaluate(points, w).0;
+        if point.x * point.x + point.y * point.y > width * width / 4.0 {
+            return None;
+        }
+        if !(point.z > z_min && point.z < z_max) {
+            return None;
+        }
+        Some((point.z, u))
+    }
+}
+
+// position and derivative of a Bézier curve
+fn evaluate(points: &[Vec3; 4], u: f32) -> (Vec3, Vec3) {
+    let [p0, p1, p2, p3] = *points;
+    let lerp = |a: Vec3, b: Vec3| a + (b - a) * u;
+    let (a, b, c) = (lerp(p0, p1), lerp(p1, p2), lerp(p2, p3));
+    let (d, e) = (lerp(a, b), lerp(b, c));
+    let derivative = if length2(&(e - d)) > 0.0 {
+        3.0 * (e - d)
+    } else {
+        // the control points of an end are merged
+        p3 - p0
+    };
+    (lerp(d, e), derivative)
+}
+
+// extremes of the curve along each axis, found where its derivative vanishes, widened by its
+// width
+fn curve_bounds(points: &[Vec3; 4], widths: &[f32; 2]) -> AABB {
+    let [p0, p1, p2, p3] = *points;
+    let radius = widths[0].max(widths[1]) / 2.0;
+    let mut min = Vec3::new(0.0, 0.0, 0.0);
+    let mut max = Vec3::new(0.0, 0.0, 0.0);
+    for a in 0..3 {
+        // the derivative is 3 (a u² + b u + c)
+        let qa = -p0[a] + 3.0 * p1[a] - 3.0 * p2[a] + p3[a];
+        let qb = 2.0 * (p0[a] - 2.0 * p1[a] + p2[a]);
+        let qc = p1[a] - p0[a];
+        let mut extremes = vec![0.0, 1.0];
+        if qa.abs() > 1e-12 {
+            let discriminant = qb * qb - 4.0 * qa * qc;
+            if discriminant >= 0.0 {
+                let root = discriminant.sqrt();
+                extremes.push((-qb + root) / (2.0 * qa));
+                extremes.push((-qb - root) / (2.0 * qa));
+            }
+        } else if qb.abs() > 1e-12 {
+            extremes.push(-qc / qb);
+        }
+        let values = extremes
+            .iter()
+            .filter(|u| (0.0..=1.0).contains(*u))
+            .map(|&u| evaluate(points, u).0[a]);
+        min[a] = values.clone().fold(f32::INFINITY, f32::min) - radius;
+        max[a] = values.fold(f32::NEG_INFINITY, f32::max) + radius;
+    }
+    AABB { min, max }.padded(AABB_PADDING)
+}
+
+// whether the strip around a piece whose control points are in the space of the ray can cross
+// the ray between z_min and z_max
+fn piece_may_hit(points: &[Vec3; 4], radius: f32, z_min: f32, z_max: f32) -> bool {
+    (0..3).all(|a| {
+        let low = points.iter().map(|p| p[a]).fold(f32::INFINITY, f32::min) - radius;
+        let high = points
+            .iter()
+            .map(|p| p[a])
+            .fold(f32::NEG_INFINITY, f32::max)
+            + radius;
+        if a == 2 {
+            high >= z_min && low <= z_max
+        } else {
+            low <= 0.0 && high >= 0.0
+        }
+    })
+}
+
+impl Hittable for Curve {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        let ray_length = length(&r.direction);
+        if ray_length == 0.0 {
+            return None;
+        }
+        let forward = r.direction / ray_length;
+        let (x_axis, y_axis) = orthonormal_basis(&forward);
+        let to_ray = |p: &Vec3| {
+            let offset = p - r.origin;
+            Vec3::new(
+                dot(&offset, &x_axis),
+                dot(&offset, &y_axis),
+                dot(&offset, &forward),
+            )
+        };
+        let points = [
+            to_ray(&self.points[0]),
+            to_ray(&self.points[1]),
+            to_ray(&self.points[2]),
+            to_ray(&self.points[3]),
+        ];
+        let (z_min, z_max) = (t_min * ray_length, t_max * ray_length);
+        let max_radius = self.widths[0].max(self.widths[1]) / 2.0;
+        if !piece_may_hit(&points, max_radius, z_min, z_max) {
+            return None;
+        }
+
+        // depth at which the pieces are within a twentieth of the width of a straight line
+        let bend = (0..2)
+            .flat_map(|i| {
+                let second_difference = points[i] - 2.0 * points[i + 1] + points[i + 2];
+                (0..3).map(move |a| second_difference[a].abs())
+            })
+            .fold(0.0, f32::max);
+        let tolerance = (max_radius / 10.0).max(f32::EPSILON);
+        let depth = if bend > 0.0 {
+            ((std::f32::consts::SQRT_2 * 6.0 * bend / (8.0 * tolerance)).log2() / 2.0).ceil() as i32
+        } else {
+            0
+        };
+        let (z, u) = self.hit_piece(
+            &points,
+            (0.0, 1.0),
+            depth.clamp(0, MAX_DEPTH),
+            (z_min, z_max),
+        )?;
+
+        let t = z / ray_length;
+        let (center, tangent) = evaluate(&self.points, u);
+        // the strip lies across the curve and the ray
+        let mut across = cross(&forward, &tangent);
+        if length2(&across) == 0.0 {
+            across = orthonormal_basis(&normalize(&tangent)).0;
+        }
+        let across = normalize(&across);
+        let facing = normalize(&cross(&across, &tangent));
+        let width = self.width(u);
+        // position across the strip, from -1 to 1
+        let side = if width > 0.0 {
+            (2.0 * dot(&(r.at(t) - center), &across) / width).clamp(-1.0, 1.0)
+        } else {
+            0.0
+        };
+        let normal = match self.mode {
+            CurveMode::Ribbon => facing,
+            // normal of a tube seen along the ray
+            CurveMode::Cylinder => {
+                normalize(&(f32::sqrt(1.0 - side * side) * facing + side * across))
+            }
+        };
+
+        let [strand_u0, strand_u1] = self.u_range;
+        let strand_u = strand_u0 + (strand_u1 - strand_u0) * u;
+        let dpdu = tangent / (strand_u1 - strand_u0);
+        let dpdv = width * across;
+        Some(
+            HitRecord::new(
+                r,
+                t,
+                &facing,
+                strand_u,
+                (side + 1.0) / 2.0,
+                Arc::clone(&self.material),
+            )
+            .with_shading_frame(&normal, &tangent)
+            .with_uv_derivatives(dpdu, dpdv),
+        )
+    }
+
+    fn bounding_box(&self, _t0: f32, _t1: f32) -> Option<AABB> {
+        Some(self.bounds)
+    }
+}
+
+impl Position for Curve {
+    fn position(&self) -> &Vec3 {
+        &self.center
+    }
+}
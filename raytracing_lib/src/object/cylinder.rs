@@ -0,0 +1,76 @@
+use super::{
+    axis_frame::AxisFrame,
+    cone::{frustum_bounds, hit_frustum},
+    Position,
+};
+use crate::{
+    aabb::AABB,
+    collision::{HitRecord, Hittable},
+};
+use crate::{material::Material, ray::Ray};
+use nalgebra_glm::{length, Vec3};
+use std::sync::Arc;
+
+// A closed cylinder going from a disk around base to one around top. The side has u going around
+// the axis and v going from the base to the top, the caps have polar texture coordinates like
+// disks.
+pub struct Cylinder {
+    frame: AxisFrame,
+    height: f32,
+    radius: f32,
+    center: Vec3,
+
+    material: Arc<Box<dyn Material>>,
+}
+
+impl std::fmt::Debug for Cylinder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format!(
+            "base:{:?} axis:{:?} h:{:?} R:{:?}",
+            self.frame.origin, self.frame.axis, self.height, self.radius
+        ))
+    }
+}
+
+impl Cylinder {
+    pub fn new(base: Vec3, top: Vec3, radius: f32, material: Arc<Box<dyn Material>>) -> Self {
+        Cylinder {
+            frame: AxisFrame::new(base, top - base),
+            height: length(&(top - base)),
+            radius,
+            center: (base + top) / 2.0,
+            material,
+        }
+    }
+}
+
+impl Hittable for Cylinder {
+    // a cylinder is a truncated cone with the same radius at both ends
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        hit_frustum(
+            &self.frame,
+            self.height,
+            self.radius,
+            self.radius,
+            &self.material,
+            r,
+            t_min,
+            t_max,
+        )
+    }
+
+    fn bounding_box(&self, _t0: f32, _t1: f32) -> Option<AABB> {
+        Some(frustum_bounds(
+            &self.frame,
+            self.height,
+            self.radius,
+            self.radius,
+        ))
+    }
+}
+
+impl Position for Cylinder {
+    fn position(&self) -> &Vec3 {
+        &self.center
+    }
+}
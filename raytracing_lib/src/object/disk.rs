@@ -0,0 +1,140 @@
+use super::{axis_frame::AxisFrame, Position};
+use crate::{
+    aabb::{AABB, AABB_PADDING},
+    collision::{HitRecord, Hittable},
+};
+use crate::{material::Material, ray::Ray};
+use nalgebra_glm::{dot, length, length2, Vec3};
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+// A flat disk facing normal. The texture coordinates are polar: u is the angle around the center
+// and v the distance to it, from 0 to 1 at the rim.
+pub struct Disk {
+    frame: AxisFrame,
+    radius: f32,
+
+    material: Arc<Box<dyn Material>>,
+}
+
+impl std::fmt::Debug for Disk {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format!(
+            "c:{:?} n:{:?} R:{:?}",
+            self.frame.origin, self.frame.axis, self.radius
+        ))
+    }
+}
+
+impl Disk {
+    pub fn new(center: Vec3, normal: Vec3, radius: f32, material: Arc<Box<dyn Material>>) -> Self {
+        Disk {
+            frame: AxisFrame::new(center, normal),
+            radius,
+            material,
+        }
+    }
+}
+
+impl Hittable for Disk {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        let (origin, direction) = self.frame.ray_to_local(r);
+        hit_cap(
+            &self.frame,
+            0.0,
+            self.radius,
+            &self.frame.axis,
+            &self.material,
+            r,
+            &origin,
+            &direction,
+            t_min,
+            t_max,
+        )
+    }
+
+    fn bounding_box(&self, _t0: f32, _t1: f32) -> Option<AABB> {
+        Some(
+            self.frame
+                .circle_bounds(0.0, self.radius)
+                .padded(AABB_PADDING),
+        )
+    }
+
+    // the disk is sampled uniformly over its area, the density is converted to solid angle
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> f32 {
+        let record = match self.hit(&Ray::new(*origin, *direction), 0.001, f32::INFINITY) {
+            Some(record) => record,
+            None => return 0.0,
+        };
+        let distance_squared = record.t * record.t * length2(direction);
+        let cosine = f32::abs(dot(direction, &self.frame.axis)) / length(direction);
+        if cosine <= 0.0 {
+            return 0.0;
+        }
+        distance_squared / (cosine * PI * self.radius * self.radius)
+    }
+
+    fn random(&self, origin: &Vec3) -> Option<Vec3> {
+        let rho = self.radius * f32::sqrt(rand::random::<f32>());
+        let phi = 2.0 * PI * rand::random::<f32>();
+        let local = Vec3::new(rho * phi.cos(), 0.0, rho * phi.sin());
+        Some(self.frame.origin + self.frame.direction_to_world(&local) - origin)
+    }
+}
+
+impl Position for Disk {
+    fn position(&self) -> &Vec3 {
+        &self.frame.origin
+    }
+}
+
+// intersection with the disk of the given radius across the axis at the given height, facing
+// outward_normal, for a ray already in local coordinates. Shared with the caps of cylinders and
+// cones
+#[allow(clippy::too_many_arguments)]
+pub(super) fn hit_cap(
+    frame: &AxisFrame,
+    height: f32,
+    radius: f32,
+    outward_normal: &Vec3,
+    material: &Arc<Box<dyn Material>>,
+    r: &Ray,
+    origin: &Vec3,
+    direction: &Vec3,
+    t_min: f32,
+    t_max: f32,
+) -> Option<HitRecord> {
+    if direction.y == 0.0 {
+        return None;
+    }
+    let t = (height - origin.y) / direction.y;
+    if !(t < t_max && t > t_min) {
+        return None;
+    }
+    let local = origin + t * direction;
+    let distance = f32::hypot(local.x, local.z);
+    if distance > radius {
+        return None;
+    }
+    let (u, tangent) = AxisFrame::azimuth(&local);
+    let dpdu = frame.direction_to_world(&(2.0 * PI * tangent));
+    // v goes from the center, where it has no direction, to the rim
+    let dpdv = if distance > 0.0 {
+        frame.direction_to_world(&Vec3::new(local.x, 0.0, local.z)) * (radius / distance)
+    } else {
+        Vec3::new(0.0, 0.0, 0.0)
+    };
+    Some(
+        HitRecord::new(
+            r,
+            t,
+            outward_normal,
+            u,
+            distance / radius,
+            Arc::clone(material),
+        )
+        .with_shading_frame(outward_normal, &dpdu)
+        .with_uv_derivatives(dpdu, dpdv),
+    )
+}
@@ -0,0 +1,394 @@
+use super::Position;
+use crate::{
+    aabb::{AABB, AABB_PADDING},
+    collision::{HitRecord, Hittable},
+};
+use crate::{material::Material, ray::Ray};
+use nalgebra_glm::{cross, dot, normalize, Vec2, Vec3};
+use std::path::Path;
+use std::sync::Arc;
+
+// cells grouped in the coarse level of the traversal, along each side
+const BLOCK_SIZE: usize = 8;
+
+// A terrain made of a grid of heights over the box starting at corner and spanning size. Samples
+// are spread evenly over x and z, from the corner to the opposite side, and their heights go from
+// corner.y for 0 to corner.y + size.y for 1. Each cell between four samples is split into two
+// triangles, shaded with normals interpolated from the slopes of the grid. Rays walk the cells
+// they cross, first in blocks of cells and then cell by cell, skipping those whose range of
+// heights they do not cross.
+// u goes along x and v along -z, so that the image a heightfield is loaded from maps onto it as a
+// texture.
+pub struct Heightfield {
+    // number of samples along x and z
+    resolution: [usize; 2],
+    // heights in world space, x first then z
+    heights: Vec<f32>,
+    normals: Vec<Vec3>,
+    corner: Vec3,
+    size: Vec3,
+    cell_size: Vec2,
+    // lowest and highest heights of each block of cells
+    block_bounds: Vec<[f32; 2]>,
+    blocks: [usize; 2],
+    bounds: AABB,
+    center: Vec3,
+
+    material: Arc<Box<dyn Material>>,
+}
+
+impl std::fmt::Debug for Heightfield {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format!(
+            "samples:{:?} corner:{:?} size:{:?}",
+            self.resolution, self.corner, self.size
+        ))
+    }
+}
+
+impl Heightfield {
+    pub fn new(
+        resolution: [usize; 2],
+        heights: Vec<f32>,
+        corner: Vec3,
+        size: Vec3,
+        material: Arc<Box<dyn Material>>,
+    ) -> Result<Self, String> {
+        let [nx, nz] = resolution;
+        if nx < 2 || nz < 2 {
+            return Err(format!(
+                "Heightfield needs at least 2x2 samples, got {}x{}",
+                nx, nz
+            ));
+        }
+        if heights.len() != nx * nz {
+            return Err(format!(
+                "Heightfield of {}x{} samples needs {} heights, got {}",
+                nx,
+                nz,
+                nx * nz,
+                heights.len()
+            ));
+        }
+        if let Some(height) = heights.iter().find(|h| !h.is_finite()) {
+            return Err(format!("Invalid height {} in heightfield", height));
+        }
+        if !(size.x > 0.0 && size.z > 0.0) {
+            return Err(format!(
+                "Heightfield must have a positive size, got {:?}",
+                size
+            ));
+        }
+
+        let heights: Vec<f32> = heights.iter().map(|h| corner.y + h * size.y).collect();
+        let cell_size = Vec2::new(size.x / (nx - 1) as f32, size.z / (nz - 1) as f32);
+        let height = |x: usize, z: usize| heights[z * nx + x];
+        // slopes by central differences, or one sided ones on the border
+        let mut normals = Vec::with_capacity(heights.len());
+        for z in 0..nz {
+            for x in 0..nx {
+                let (x0, x1) = (x.saturating_sub(1), (x + 1).min(nx - 1));
+                let (z0, z1) = (z.saturating_sub(1), (z + 1).min(nz - 1));
+                let slope_x = (height(x1, z) - height(x0, z)) / ((x1 - x0) as f32 * cell_size.x);
+                let slope_z = (height(x, z1) - height(x, z0)) / ((z1 - z0) as f32 * cell_size.y);
+                normals.push(normalize(&Vec3::new(-slope_x, 1.0, -slope_z)));
+            }
+        }
+
+        let blocks = [(nx - 2) / BLOCK_SIZE + 1, (nz - 2) / BLOCK_SIZE + 1];
+        let mut block_bounds = vec![[f32::INFINITY, f32::NEG_INFINITY]; blocks[0] * blocks[1]];
+        for z in 0..nz {
+            for x in 0..nx {
+                // samples on the side of a block also belong to the cells of the previous one
+                for bz in (z.saturating_sub(1) / BLOCK_SIZE)..=((z.min(nz - 2)) / BLOCK_SIZE) {
+                    for bx in (x.saturating_sub(1) / BLOCK_SIZE)..=((x.min(nx - 2)) / BLOCK_SIZE) {
+                        let bounds = &mut block_bounds[bz * blocks[0] + bx];
+                        bounds[0] = bounds[0].min(height(x, z));
+                        bounds[1] = bounds[1].max(height(x, z));
+                    }
+                }
+            }
+        }
+
+        let (lowest, highest) = heights.iter().fold(
+            (f32::INFINITY, f32::NEG_INFINITY),
+            |(lowest, highest), h| (lowest.min(*h), highest.max(*h)),
+        );
+        let bounds = AABB {
+            min: Vec3::new(corner.x, lowest, corner.z),
+            max: Vec3::new(corner.x + size.x, highest, corner.z + size.z),
+        }
+        .padded(AABB_PADDING);
+        Ok(Heightfield {
+            resolution,
+            heights,
+            normals,
+            corner,
+            size,
+            cell_size,
+            block_bounds,
+            blocks,
+            center: (bounds.min + bounds.max) / 2.0,
+            bounds,
+            material,
+        })
+    }
+
+    // Loads the heights from a grayscale image, black is the bottom of the box and white its top.
+    // Values are used as they are stored, without sRGB decoding, and 16 bits images keep their
+    // precision. The top row of the image is at corner.z.
+    pub fn from_image<P: AsRef<Path>>(
+        path: &P,
+        corner: Vec3,
+        size: Vec3,
+        material: Arc<Box<dyn Material>>,
+    ) -> Result<Self, String> {
+        let path = path.as_ref();
+        let image = image::open(path)
+            .map_err(|err| format!("Cannot load heightfield {}: {}", path.display(), err))?
+            .into_luma16();
+        let resolution = [image.width() as usize, image.height() as usize];
+        let heights = image
+            .pixels()
+            .map(|p| p[0] as f32 / u16::MAX as f32)
+            .collect();
+        Heightfield::new(resolution, heights, corner, size, material)
+            .map_err(|err| format!("Heightfield {}: {}", path.display(), err))
+    }
+
+    // Loads a text file starting with the number of samples along x and z, followed by the
+    // heights in the order of the grid, all separated by whitespace. Lines starting with # are
+    // comments.
+    pub fn from_file<P: AsRef<Path>>(
+        path: &P,
+        corner: Vec3,
+        size: Vec3,
+        material: Arc<Box<dyn Material>>,
+    ) -> Result<Self, String> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| format!("Cannot load heightfield {}: {}", path.display(), err))?;
+        let mut tokens = content
+            .lines()
+            .filter(|line| !line.trim_start().starts_with('#'))
+            .flat_map(str::split_whitespace);
+        let in_file = |err: String| format!("Heightfield {}: {}", path.display(), err);
+
+        let mut resolution = [0; 2];
+        for dimension in resolution.iter_mut() {
+            let token = tokens
+                .next()
+                .ok_or_else(|| in_file("missing grid size".into()))?;
+            *dimension = token
+                .parse()
+                .map_err(|_| in_file(format!("invalid grid size {}", token)))?;
+        }
+        let heights = tokens
+            .map(|token| {
+                token
+                    .parse()
+                    .map_err(|_| in_file(format!("invalid height {}", token)))
+            })
+            .collect::<Result<Vec<f32>, String>>()?;
+        Heightfield::new(resolution, heights, corner, size, material).map_err(in_file)
+    }
+
+    fn index(&self, x: usize, z: usize) -> usize {
+        z * self.resolution[0] + x
+    }
+
+    fn sample(&self, x: usize, z: usize) -> Vec3 {
+        Vec3::new(
+            self.corner.x + x as f32 * self.cell_size.x,
+            self.heights[self.index(x, z)],
+            self.corner.z + z as f32 * self.cell_size.y,
+        )
+    }
+
+    // Visits the cells of a grid over the heightfield crossed by the ray between t_start and
+    // t_end, in order, with the part of the ray inside each of them, until visit returns a hit.
+    // Cells have the given size, only the ones between first and last are visited.
+    fn walk<F>(
+        &self,
+        r: &Ray,
+        (t_start, t_end): (f32, f32),
+        cell_size: Vec2,
+        first: [usize; 2],
+        last: [usize; 2],
+        mut visit: F,
+    ) -> Option<HitRecord>
+    where
+        F: FnMut([usize; 2], f32, f32) -> Option<HitRecord>,
+    {
+        let start = r.at(t_start);
+        let mut cell = [0; 2];
+        let mut step = [0; 2];
+        // distance along the ray to the next side of a cell, and between two of them
+        let mut t_next = [f32::INFINITY; 2];
+        let mut t_delta = [f32::INFINITY; 2];
+        for (k, a) in [0, 2].iter().cloned().enumerate() {
+            let position = ((start[a] - self.corner[a]) / cell_size[k]).max(0.0);
+            cell[k] = (position as usize).max(first[k]).min(last[k]);
+            if r.direction[a] > 0.0 {
+                step[k] = 1;
+                let side = self.corner[a] + (cell[k] + 1) as f32 * cell_size[k];
+                t_next[k] = (side - r.origin[a]) / r.direction[a];
+                t_delta[k] = cell_size[k] / r.direction[a];
+            } else if r.direction[a] < 0.0 {
+                step[k] = -1;
+                let side = self.corner[a] + cell[k] as f32 * cell_size[k];
+                t_next[k] = (side - r.origin[a]) / r.direction[a];
+                t_delta[k] = -cell_size[k] / r.direction[a];
+            }
+        }
+
+        let mut t = t_start;
+        loop {
+            let t_exit = t_next[0].min(t_next[1]).min(t_end);
+            if let Some(record) = visit(cell, t, t_exit) {
+                return Some(record);
+            }
+            if t_exit >= t_end {
+                return None;
+            }
+            let k = if t_next[0] < t_next[1] { 0 } else { 1 };
+            if (step[k] > 0 && cell[k] == last[k]) || (step[k] < 0 && cell[k] == first[k]) {
+                return None;
+            }
+            cell[k] = (cell[k] as isize + step[k]) as usize;
+            t = t_next[k];
+            t_next[k] += t_delta[k];
+        }
+    }
+
+    // whether the ray goes through the heights between low and high while it is between t0 and t1
+    fn crosses(r: &Ray, t0: f32, t1: f32, [low, high]: [f32; 2]) -> bool {
+        let (y0, y1) = (r.at(t0).y, r.at(t1).y);
+        y0.min(y1) <= high + AABB_PADDING && y0.max(y1) >= low - AABB_PADDING
+    }
+
+    fn hit_cell(&self, r: &Ray, [x, z]: [usize; 2], t_min: f32, t_max: f32) -> Option<HitRecord> {
+        // both triangles of the cell face +y
+        let triangles = [
+            [(x, z), (x, z + 1), (x + 1, z)],
+            [(x + 1, z + 1), (x + 1, z), (x, z + 1)],
+        ];
+        let mut closest = None;
+        for (index, vertices) in triangles.iter().enumerate() {
+            let t_far = closest.map_or(t_max, |(t, _, _)| t);
+            if let Some((t, barycentric)) = self.hit_triangle(r, vertices, t_min, t_far) {
+                closest = Some((t, index, barycentric));
+            }
+        }
+        let (t, index, barycentric) = closest?;
+        let vertices = triangles[index];
+
+        let [p0, p1, p2] = vertices.map(|(x, z)| self.sample(x, z));
+        let face_normal = normalize(&cross(&(p1 - p0), &(p2 - p0)));
+        let [n0, n1, n2] = vertices.map(|(x, z)| self.normals[self.index(x, z)]);
+        let normal = normalize(&(barycentric.x * n0 + barycentric.y * n1 + barycentric.z * n2));
+        let point = r.at(t);
+        let u = ((point.x - self.corner.x) / self.size.x).clamp(0.0, 1.0);
+        let v = 1.0 - ((point.z - self.corner.z) / self.size.z).clamp(0.0, 1.0);
+        // u and v move along x and -z, and up or down the slopes of the triangle
+        let dpdu = self.size.x * Vec3::new(1.0, -face_normal.x / face_normal.y, 0.0);
+        let dpdv = self.size.z * Vec3::new(0.0, face_normal.z / face_normal.y, -1.0);
+        Some(
+            HitRecord::new(r, t, &face_normal, u, v, Arc::clone(&self.material))
+                .with_shading_frame(&normal, &dpdu)
+                .with_uv_derivatives(dpdu, dpdv),
+        )
+    }
+
+    // Möller-Trumbore intersection, returns the distance and the barycentric coordinates of the
+    // hit point. The bounds of the barycentric coordinates are inclusive so that rays cannot slip
+    // between neighbouring triangles.
+    fn hit_triangle(
+        &self,
+        r: &Ray,
+        vertices: &[(usize, usize); 3],
+        t_min: f32,
+        t_max: f32,
+    ) -> Option<(f32, Vec3)> {
+        let v0 = self.sample(vertices[0].0, vertices[0].1);
+        let edge1 = self.sample(vertices[1].0, vertices[1].1) - v0;
+        let edge2 = self.sample(vertices[2].0, vertices[2].1) - v0;
+        let p = cross(&r.direction, &edge2);
+        let determinant = dot(&edge1, &p);
+        if determinant.abs() < 1e-12 {
+            return None;
+        }
+        let s = r.origin - v0;
+        let b1 = dot(&s, &p) / determinant;
+        if !(0.0..=1.0).contains(&b1) {
+            return None;
+        }
+        let q = cross(&s, &edge1);
+        let b2 = dot(&r.direction, &q) / determinant;
+        if b2 < 0.0 || b1 + b2 > 1.0 {
+            return None;
+        }
+        let t = dot(&edge2, &q) / determinant;
+        if !(t < t_max && t > t_min) {
+            return None;
+        }
+        Some((t, Vec3::new(1.0 - b1 - b2, b1, b2)))
+    }
+}
+
+impl Hittable for Heightfield {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        let range = self.bounds.intersect(r, t_min, t_max)?;
+        let [nx, nz] = self.resolution;
+        let last_cell = [nx - 2, nz - 2];
+        let block_size = self.cell_size * BLOCK_SIZE as f32;
+        let last_block = [self.blocks[0] - 1, self.blocks[1] - 1];
+
+        self.walk(r, range, block_size, [0, 0], last_block, |block, t0, t1| {
+            if !Heightfield::crosses(
+                r,
+                t0,
+                t1,
+                self.block_bounds[block[1] * self.blocks[0] + block[0]],
+            ) {
+                return None;
+            }
+            let first = [block[0] * BLOCK_SIZE, block[1] * BLOCK_SIZE];
+            let last = [
+                (first[0] + BLOCK_SIZE - 1).min(last_cell[0]),
+                (first[1] + BLOCK_SIZE - 1).min(last_cell[1]),
+            ];
+            self.walk(
+                r,
+                (t0, t1),
+                self.cell_size,
+                first,
+                last,
+                |[x, z], t0, t1| {
+                    let corners = [
+                        self.heights[self.index(x, z)],
+                        self.heights[self.index(x + 1, z)],
+                        self.heights[self.index(x, z + 1)],
+                        self.heights[self.index(x + 1, z + 1)],
+                    ];
+                    let low = corners.iter().cloned().fold(f32::INFINITY, f32::min);
+                    let high = corners.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+                    if !Heightfield::crosses(r, t0, t1, [low, high]) {
+                        return None;
+                    }
+                    self.hit_cell(r, [x, z], t_min, t_max)
+                },
+            )
+        })
+    }
+
+    fn bounding_box(&self, _t0: f32, _t1: f32) -> Option<AABB> {
+        Some(self.bounds)
+    }
+}
+
+impl Position for Heightfield {
+    fn position(&self) -> &Vec3 {
+        &self.center
+    }
+}
@@ -0,0 +1,205 @@
+use super::transform::AffineMaps;
+use crate::{
+    aabb::AABB,
+    collision::{HitRecord, Hittable},
+    ray::Ray,
+};
+use nalgebra_glm::{
+    length, quat_angle_axis, quat_conjugate, quat_dot, quat_identity, quat_normalize, quat_to_mat4,
+    scaling, translation, Quat, Vec3,
+};
+use std::sync::Arc;
+
+// number of poses sampled between two keyframes to bound the motion of an object
+const BOX_SAMPLES: usize = 16;
+
+// pose of an object at a given time: it is scaled, then rotated, then translated
+#[derive(Debug, Copy, Clone)]
+pub struct Keyframe {
+    pub time: f32,
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+}
+
+impl Keyframe {
+    pub fn new(time: f32) -> Self {
+        Keyframe {
+            time,
+            translation: Vec3::new(0.0, 0.0, 0.0),
+            rotation: quat_identity(),
+            scale: Vec3::new(1.0, 1.0, 1.0),
+        }
+    }
+
+    pub fn translation(mut self, translation: Vec3) -> Self {
+        self.translation = translation;
+        self
+    }
+
+    // angle in degrees, counterclockwise around the axis
+    pub fn rotation(mut self, axis: Vec3, angle: f32) -> Self {
+        self.rotation = quat_angle_axis(angle.to_radians(), &axis);
+        self
+    }
+
+    pub fn scale(mut self, scale: Vec3) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    fn maps(&self) -> AffineMaps {
+        let matrix =
+            translation(&self.translation) * quat_to_mat4(&self.rotation) * scaling(&self.scale);
+        // the inverse of each step is known, no need for a generic matrix inversion
+        let inverse = scaling(&self.scale.map(|s| 1.0 / s))
+            * quat_to_mat4(&quat_conjugate(&self.rotation))
+            * translation(&-self.translation);
+        AffineMaps::new(matrix, inverse)
+    }
+
+    // translation and scale are interpolated linearly, rotation spherically
+    fn interpolate(&self, next: &Keyframe, fraction: f32) -> Keyframe {
+        Keyframe {
+            time: self.time + fraction * (next.time - self.time),
+            translation: self.translation + fraction * (next.translation - self.translation),
+            rotation: slerp(&self.rotation, &next.rotation, fraction),
+            scale: self.scale + fraction * (next.scale - self.scale),
+        }
+    }
+}
+
+// Spherical linear interpolation between two unit quaternions, along the shortest arc. The
+// nalgebra version panics for opposite rotations.
+fn slerp(q0: &Quat, q1: &Quat, fraction: f32) -> Quat {
+    let mut q1 = *q1;
+    let mut cos_theta = quat_dot(q0, &q1);
+    if cos_theta < 0.0 {
+        q1 = -q1;
+        cos_theta = -cos_theta;
+    }
+    // nearly identical rotations, sin(theta) is too small to divide by
+    if cos_theta > 0.9995 {
+        return quat_normalize(&(q0 * (1.0 - fraction) + q1 * fraction));
+    }
+    let theta = f32::acos(cos_theta);
+    (q0 * f32::sin((1.0 - fraction) * theta) + q1 * f32::sin(fraction * theta)) / f32::sin(theta)
+}
+
+// angle of the rotation going from q0 to q1
+fn rotation_angle(q0: &Quat, q1: &Quat) -> f32 {
+    2.0 * f32::acos(quat_dot(q0, q1).abs().min(1.0))
+}
+
+// An object moving along keyframes, its pose at the time of a ray is interpolated between the
+// keyframes around it. Before the first keyframe and after the last one the object stays still.
+pub struct KeyframedTransform {
+    object: Arc<dyn Hittable>,
+    // sorted by time
+    keyframes: Vec<Keyframe>,
+}
+
+impl std::fmt::Debug for KeyframedTransform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format!("keyframes:{:?}", self.keyframes))
+    }
+}
+
+impl KeyframedTransform {
+    pub fn new(object: Arc<dyn Hittable>, mut keyframes: Vec<Keyframe>) -> Result<Self, String> {
+        if keyframes.is_empty() {
+            return Err("A keyframed transform needs at least one keyframe".into());
+        }
+        for keyframe in keyframes.iter() {
+            if !keyframe.time.is_finite() {
+                return Err(format!("Invalid keyframe time {}", keyframe.time));
+            }
+            if keyframe.scale.iter().any(|s| *s == 0.0 || !s.is_finite()) {
+                return Err(format!(
+                    "Keyframe at time {} has a singular scale {:?}",
+                    keyframe.time, keyframe.scale
+                ));
+            }
+            if keyframe.rotation.coords.iter().any(|c| !c.is_finite()) {
+                return Err(format!(
+                    "Keyframe at time {} has an invalid rotation",
+                    keyframe.time
+                ));
+            }
+        }
+        keyframes.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+        Ok(KeyframedTransform { object, keyframes })
+    }
+
+    pub fn pose(&self, time: f32) -> Keyframe {
+        let next = self.keyframes.partition_point(|k| k.time <= time);
+        if next == 0 {
+            return Keyframe {
+                time,
+                ..self.keyframes[0]
+            };
+        }
+        if next == self.keyframes.len() {
+            return Keyframe {
+                time,
+                ..self.keyframes[next - 1]
+            };
+        }
+        let (previous, next) = (&self.keyframes[next - 1], &self.keyframes[next]);
+        previous.interpolate(next, (time - previous.time) / (next.time - previous.time))
+    }
+}
+
+impl Hittable for KeyframedTransform {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        self.pose(r.time)
+            .maps()
+            .hit(self.object.as_ref(), r, t_min, t_max)
+    }
+
+    // union of the boxes of poses sampled over the interval, padded by how far the rotation can
+    // take the object away from them between two samples
+    fn bounding_box(&self, t0: f32, t1: f32) -> Option<AABB> {
+        let aabb = self.object.bounding_box(t0, t1)?;
+        let (t0, t1) = (f32::min(t0, t1), f32::max(t0, t1));
+
+        let mut breakpoints = vec![t0];
+        breakpoints.extend(
+            self.keyframes
+                .iter()
+                .map(|k| k.time)
+                .filter(|time| *time > t0 && *time < t1),
+        );
+        breakpoints.push(t1);
+
+        let mut poses = vec![self.pose(t0)];
+        for pair in breakpoints.windows(2) {
+            for i in 1..=BOX_SAMPLES {
+                let fraction = i as f32 / BOX_SAMPLES as f32;
+                poses.push(self.pose(pair[0] + fraction * (pair[1] - pair[0])));
+            }
+        }
+
+        let max_angle = poses
+            .windows(2)
+            .map(|pair| rotation_angle(&pair[0].rotation, &pair[1].rotation))
+            .fold(0.0, f32::max);
+        let max_scale = poses
+            .iter()
+            .flat_map(|pose| pose.scale.iter().map(|s| s.abs()).collect::<Vec<f32>>())
+            .fold(0.0, f32::max);
+        let farthest_corner = aabb.min.abs().sup(&aabb.max.abs());
+        let radius = max_scale * length(&farthest_corner);
+        // distance between an arc of a circle of this radius and its chord
+        let margin = Vec3::repeat(radius * (1.0 - f32::cos(max_angle / 2.0)));
+
+        poses
+            .iter()
+            .map(|pose| pose.maps().bounding_box(&aabb))
+            .reduce(|a, b| AABB::surrounding_box(&a, &b))
+            .map(|aabb| AABB {
+                min: aabb.min - margin,
+                max: aabb.max + margin,
+            })
+    }
+}
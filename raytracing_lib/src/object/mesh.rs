@@ -0,0 +1,175 @@
+use super::Triangle;
+use crate::{
+    aabb::AABB,
+    bvh::BVHNode,
+    collision::{HitRecord, Hittable},
+};
+use crate::{material::Material, ray::Ray};
+use nalgebra_glm::{cross, dot, length, length2, Vec2, Vec3};
+use std::sync::Arc;
+
+// vertex and index buffers shared by all the triangles of a mesh
+pub(super) struct MeshData {
+    pub(super) positions: Vec<Vec3>,
+    pub(super) normals: Option<Vec<Vec3>>,
+    pub(super) uvs: Option<Vec<Vec2>>,
+    // linear colors tinting the albedo of the material
+    pub(super) colors: Option<Vec<Vec3>>,
+    pub(super) indices: Vec<[usize; 3]>,
+}
+
+pub struct TriangleMesh {
+    data: Arc<MeshData>,
+    material: Arc<Box<dyn Material>>,
+
+    // triangles of the mesh are stored in their own BVH, the mesh is then a single node of the world's BVH
+    bvh: BVHNode,
+    // running sum of the triangle areas, to sample the mesh uniformly over its area
+    cumulative_areas: Vec<f32>,
+}
+
+impl std::fmt::Debug for TriangleMesh {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format!(
+            "vertices:{:?} triangles:{:?} AABB:{:?}",
+            self.data.positions.len(),
+            self.data.indices.len(),
+            self.bvh.aabb
+        ))
+    }
+}
+
+impl TriangleMesh {
+    pub fn new(
+        positions: Vec<Vec3>,
+        indices: Vec<[usize; 3]>,
+        normals: Option<Vec<Vec3>>,
+        uvs: Option<Vec<Vec2>>,
+        colors: Option<Vec<Vec3>>,
+        material: Arc<Box<dyn Material>>,
+    ) -> Result<Self, String> {
+        if let Some(index) = indices.iter().flatten().find(|&&i| i >= positions.len()) {
+            return Err(format!(
+                "Vertex index {} out of bounds for a mesh with {} vertices",
+                index,
+                positions.len()
+            ));
+        }
+        if matches!(&normals, Some(normals) if normals.len() != positions.len()) {
+            return Err("Mesh must have exactly one normal per vertex".into());
+        }
+        if matches!(&uvs, Some(uvs) if uvs.len() != positions.len()) {
+            return Err("Mesh must have exactly one uv per vertex".into());
+        }
+        if matches!(&colors, Some(colors) if colors.len() != positions.len()) {
+            return Err("Mesh must have exactly one color per vertex".into());
+        }
+
+        let data = Arc::new(MeshData {
+            positions,
+            normals,
+            uvs,
+            colors,
+            indices,
+        });
+        let triangles: Vec<Arc<dyn Hittable>> = (0..data.indices.len())
+            .map(|index| {
+                Arc::new(Triangle::from_mesh(
+                    Arc::clone(&data),
+                    index,
+                    Arc::clone(&material),
+                )) as Arc<dyn Hittable>
+            })
+            .collect();
+        // the triangles of a mesh do not move, any time interval gives the same boxes
+        let bvh = BVHNode::new(&triangles[..], 0.0, 0.0)?;
+        let cumulative_areas = data
+            .indices
+            .iter()
+            .scan(0.0, |total, &[i0, i1, i2]| {
+                let (v0, v1, v2) = (data.positions[i0], data.positions[i1], data.positions[i2]);
+                *total += 0.5 * length(&cross(&(v1 - v0), &(v2 - v0)));
+                Some(*total)
+            })
+            .collect();
+
+        Ok(TriangleMesh {
+            data,
+            material,
+            bvh,
+            cumulative_areas,
+        })
+    }
+
+    pub fn positions(&self) -> &[Vec3] {
+        &self.data.positions
+    }
+
+    pub fn normals(&self) -> Option<&[Vec3]> {
+        self.data.normals.as_deref()
+    }
+
+    pub fn uvs(&self) -> Option<&[Vec2]> {
+        self.data.uvs.as_deref()
+    }
+
+    pub fn colors(&self) -> Option<&[Vec3]> {
+        self.data.colors.as_deref()
+    }
+
+    pub fn indices(&self) -> &[[usize; 3]] {
+        &self.data.indices
+    }
+
+    // iterates over the triangles of the mesh, they share the mesh buffers
+    pub fn triangles(&self) -> impl Iterator<Item = Triangle> + '_ {
+        (0..self.data.indices.len()).map(move |index| {
+            Triangle::from_mesh(Arc::clone(&self.data), index, Arc::clone(&self.material))
+        })
+    }
+}
+
+impl Hittable for TriangleMesh {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        self.bvh.hit(r, t_min, t_max)
+    }
+
+    fn bounding_box(&self, t0: f32, t1: f32) -> Option<AABB> {
+        self.bvh.bounding_box(t0, t1)
+    }
+
+    // the mesh is sampled uniformly over its area, the densities of all the triangles the direction
+    // goes through add up since any of them could have been sampled
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> f32 {
+        let area = match self.cumulative_areas.last() {
+            Some(&area) if area > 0.0 => area,
+            _ => return 0.0,
+        };
+        let ray = Ray::new(*origin, *direction);
+        let mut pdf = 0.0;
+        let mut t_min = 0.001;
+        while let Some(record) = self.hit(&ray, t_min, f32::INFINITY) {
+            let distance_squared = record.t * record.t * length2(direction);
+            let cosine = f32::abs(dot(direction, &record.geometric_normal)) / length(direction);
+            if cosine > 0.0 {
+                pdf += distance_squared / (cosine * area);
+            }
+            t_min = record.t + 0.001;
+        }
+        pdf
+    }
+
+    fn random(&self, origin: &Vec3) -> Option<Vec3> {
+        let area = *self.cumulative_areas.last()?;
+        if area <= 0.0 {
+            return None;
+        }
+        let target = rand::random::<f32>() * area;
+        let index = self
+            .cumulative_areas
+            .partition_point(|&total| total <= target)
+            .min(self.cumulative_areas.len() - 1);
+        Triangle::from_mesh(Arc::clone(&self.data), index, Arc::clone(&self.material))
+            .random(origin)
+    }
+}
@@ -1,9 +1,44 @@
 use nalgebra_glm::Vec3;
 
+mod axis_frame;
+mod cone;
+mod constant_medium;
+mod csg;
+mod cuboid;
+mod curve;
+mod cylinder;
+mod disk;
+mod heightfield;
+mod keyframed_transform;
+mod mesh;
+mod moving_sphere;
+mod plane;
+mod quad;
+mod signed_distance_field;
 mod sphere;
+mod torus;
+mod transform;
+mod triangle;
 
 pub trait Position {
     fn position(&self) -> &Vec3;
 }
 
+pub use cone::Cone;
+pub use constant_medium::ConstantMedium;
+pub use csg::{Csg, CsgOperation};
+pub use cuboid::Cuboid;
+pub use curve::{Curve, CurveMode};
+pub use cylinder::Cylinder;
+pub use disk::Disk;
+pub use heightfield::Heightfield;
+pub use keyframed_transform::{Keyframe, KeyframedTransform};
+pub use mesh::TriangleMesh;
+pub use moving_sphere::MovingSphere;
+pub use plane::Plane;
+pub use quad::Quad;
+pub use signed_distance_field::SignedDistanceField;
 pub use sphere::Sphere;
+pub use torus::Torus;
+pub use transform::Transform;
+pub use triangle::Triangle;
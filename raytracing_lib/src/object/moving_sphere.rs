@@ -0,0 +1,86 @@
+use super::sphere::hit_sphere;
+use crate::{
+    aabb::AABB,
+    collision::{HitRecord, Hittable},
+};
+use crate::{material::Material, ray::Ray};
+use nalgebra_glm::Vec3;
+use std::sync::Arc;
+
+// A sphere whose center goes in a straight line from center0 at time0 to center1 at time1. It
+// stays at center0 before time0 and at center1 after time1.
+pub struct MovingSphere {
+    center0: Vec3,
+    center1: Vec3,
+    time0: f32,
+    time1: f32,
+    radius: f32,
+
+    material: Arc<Box<dyn Material>>,
+}
+
+impl std::fmt::Debug for MovingSphere {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format!(
+            "c0:{:?} t0:{:?} c1:{:?} t1:{:?} R:{:?}",
+            self.center0, self.time0, self.center1, self.time1, self.radius
+        ))
+    }
+}
+
+impl MovingSphere {
+    pub fn new(
+        center0: Vec3,
+        center1: Vec3,
+        time0: f32,
+        time1: f32,
+        radius: f32,
+        material: Arc<Box<dyn Material>>,
+    ) -> Self {
+        MovingSphere {
+            center0,
+            center1,
+            time0,
+            time1,
+            radius,
+            material,
+        }
+    }
+
+    pub fn center(&self, time: f32) -> Vec3 {
+        if self.time1 <= self.time0 {
+            return self.center0;
+        }
+        let fraction = ((time - self.time0) / (self.time1 - self.time0)).clamp(0.0, 1.0);
+        self.center0 + fraction * (self.center1 - self.center0)
+    }
+}
+
+impl Hittable for MovingSphere {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        hit_sphere(
+            &self.center(r.time),
+            self.radius,
+            &self.material,
+            r,
+            t_min,
+            t_max,
+        )
+    }
+
+    // the center moves in a straight line, the spheres at both ends of the interval bound it
+    fn bounding_box(&self, t0: f32, t1: f32) -> Option<AABB> {
+        let radius = Vec3::new(self.radius, self.radius, self.radius);
+        let (start, end) = (self.center(t0), self.center(t1));
+        Some(AABB::surrounding_box(
+            &AABB {
+                min: start - radius,
+                max: start + radius,
+            },
+            &AABB {
+                min: end - radius,
+                max: end + radius,
+            },
+        ))
+    }
+}
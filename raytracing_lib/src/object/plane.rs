@@ -0,0 +1,76 @@
+use super::Position;
+use crate::{
+    aabb::AABB,
+    collision::{HitRecord, Hittable},
+};
+use crate::{material::Material, ray::Ray, utils::orthonormal_basis};
+use nalgebra_glm::{dot, normalize, Vec3};
+use std::sync::Arc;
+
+// An infinite plane. It has no bounding box so the world keeps it out of its BVH and tests it
+// against every ray.
+pub struct Plane {
+    point: Vec3,
+    normal: Vec3,
+    // directions of the u and v texture coordinates, which are distances along them from point
+    tangent: Vec3,
+    bitangent: Vec3,
+
+    material: Arc<Box<dyn Material>>,
+}
+
+impl std::fmt::Debug for Plane {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format!("p:{:?} n:{:?}", self.point, self.normal))
+    }
+}
+
+impl Plane {
+    pub fn new(point: Vec3, normal: Vec3, material: Arc<Box<dyn Material>>) -> Self {
+        let normal = normalize(&normal);
+        let (tangent, bitangent) = orthonormal_basis(&normal);
+        Plane {
+            point,
+            normal,
+            tangent,
+            bitangent,
+            material,
+        }
+    }
+}
+
+impl Hittable for Plane {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        let denominator = dot(&self.normal, &r.direction);
+        if denominator.abs() < 1e-8 {
+            return None;
+        }
+        let t = dot(&(self.point - r.origin), &self.normal) / denominator;
+        if !(t < t_max && t > t_min) {
+            return None;
+        }
+        let offset = r.at(t) - self.point;
+        Some(
+            HitRecord::new(
+                r,
+                t,
+                &self.normal,
+                dot(&offset, &self.tangent),
+                dot(&offset, &self.bitangent),
+                Arc::clone(&self.material),
+            )
+            .with_shading_frame(&self.normal, &self.tangent)
+            .with_uv_derivatives(self.tangent, self.bitangent),
+        )
+    }
+
+    fn bounding_box(&self, _t0: f32, _t1: f32) -> Option<AABB> {
+        None
+    }
+}
+
+impl Position for Plane {
+    fn position(&self) -> &Vec3 {
+        &self.point
+    }
+}
@@ -0,0 +1,106 @@
+use crate::{
+    aabb::{AABB, AABB_PADDING},
+    collision::{HitRecord, Hittable},
+};
+use crate::{material::Material, ray::Ray};
+use nalgebra_glm::{cross, dot, length, length2, normalize, Vec3};
+use std::sync::Arc;
+
+// A parallelogram with a corner at q and sides u and v, the texture coordinates go from 0 to 1
+// along both sides. Its normal is cross(u, v).
+pub struct Quad {
+    q: Vec3,
+    u: Vec3,
+    v: Vec3,
+    normal: Vec3,
+    // cross(u, v) / |cross(u, v)|², turns a point of the plane into coordinates along u and v
+    w: Vec3,
+    area: f32,
+
+    material: Arc<Box<dyn Material>>,
+}
+
+impl std::fmt::Debug for Quad {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format!("q:{:?} u:{:?} v:{:?}", self.q, self.u, self.v))
+    }
+}
+
+impl Quad {
+    pub fn new(q: Vec3, u: Vec3, v: Vec3, material: Arc<Box<dyn Material>>) -> Self {
+        let n = cross(&u, &v);
+        Quad {
+            q,
+            u,
+            v,
+            normal: normalize(&n),
+            w: n / length2(&n),
+            area: length(&n),
+            material,
+        }
+    }
+}
+
+impl Hittable for Quad {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        let denominator = dot(&self.normal, &r.direction);
+        if denominator.abs() < 1e-8 {
+            return None;
+        }
+        let t = dot(&(self.q - r.origin), &self.normal) / denominator;
+        if !(t < t_max && t > t_min) {
+            return None;
+        }
+
+        let offset = r.at(t) - self.q;
+        let alpha = dot(&self.w, &cross(&offset, &self.v));
+        let beta = dot(&self.w, &cross(&self.u, &offset));
+        if !(0.0..=1.0).contains(&alpha) || !(0.0..=1.0).contains(&beta) {
+            return None;
+        }
+        Some(
+            HitRecord::new(r, t, &self.normal, alpha, beta, Arc::clone(&self.material))
+                .with_shading_frame(&self.normal, &self.u)
+                .with_uv_derivatives(self.u, self.v),
+        )
+    }
+
+    fn bounding_box(&self, _t0: f32, _t1: f32) -> Option<AABB> {
+        let corners = [self.q + self.u, self.q + self.v, self.q + self.u + self.v];
+        let aabb = corners.iter().fold(
+            AABB {
+                min: self.q,
+                max: self.q,
+            },
+            |aabb, corner| {
+                AABB::surrounding_box(
+                    &aabb,
+                    &AABB {
+                        min: *corner,
+                        max: *corner,
+                    },
+                )
+            },
+        );
+        Some(aabb.padded(AABB_PADDING))
+    }
+
+    // the quad is sampled uniformly over its area, the density is converted to solid angle
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> f32 {
+        let record = match self.hit(&Ray::new(*origin, *direction), 0.001, f32::INFINITY) {
+            Some(record) => record,
+            None => return 0.0,
+        };
+        let distance_squared = record.t * record.t * length2(direction);
+        let cosine = f32::abs(dot(direction, &self.normal)) / length(direction);
+        if cosine <= 0.0 {
+            return 0.0;
+        }
+        distance_squared / (cosine * self.area)
+    }
+
+    fn random(&self, origin: &Vec3) -> Option<Vec3> {
+        let point = self.q + rand::random::<f32>() * self.u + rand::random::<f32>() * self.v;
+        Some(point - origin)
+    }
+}
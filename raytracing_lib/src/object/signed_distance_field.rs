@@ -0,0 +1,108 @@
+use crate::{
+    aabb::AABB,
+    collision::{HitRecord, Hittable},
+    sdf::DistanceFunction,
+};
+use crate::{material::Material, ray::Ray};
+use nalgebra_glm::{length, normalize, Vec3};
+use std::sync::Arc;
+
+// distance to the surface under which a ray is considered to hit it
+const HIT_DISTANCE: f32 = 1e-4;
+// after this many steps the ray is considered to miss, it happens when grazing the surface
+const MAX_STEPS: usize = 256;
+// step of the finite differences giving the normal
+const NORMAL_DELTA: f32 = 1e-4;
+
+// A surface defined by a signed distance function (see the sdf module), intersected by sphere
+// tracing: the ray steps forward by the distance to the surface, which can never overshoot it.
+// The function has no bounds, so the box inside which the surface lies must be given; rays are
+// only traced inside it.
+pub struct SignedDistanceField {
+    function: Box<dyn DistanceFunction>,
+    bounds: AABB,
+
+    material: Arc<Box<dyn Material>>,
+}
+
+impl std::fmt::Debug for SignedDistanceField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format!("bounds:{:?}", self.bounds))
+    }
+}
+
+impl SignedDistanceField {
+    // the corners of the bounding box can be given in any order
+    pub fn new(
+        function: impl DistanceFunction + 'static,
+        a: Vec3,
+        b: Vec3,
+        material: Arc<Box<dyn Material>>,
+    ) -> Self {
+        SignedDistanceField {
+            function: Box::new(function),
+            bounds: AABB {
+                min: a.inf(&b),
+                max: a.sup(&b),
+            },
+            material,
+        }
+    }
+
+    // gradient of the distance by central differences, it points out of the surface
+    fn normal(&self, point: &Vec3) -> Vec3 {
+        let gradient = Vec3::from_fn(|axis, _| {
+            let mut offset = Vec3::new(0.0, 0.0, 0.0);
+            offset[axis] = NORMAL_DELTA;
+            self.function.distance(&(point + offset)) - self.function.distance(&(point - offset))
+        });
+        normalize(&gradient)
+    }
+}
+
+impl Hittable for SignedDistanceField {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        let (t_start, t_end) = self.bounds.intersect(r, t_min, t_max)?;
+        let ray_length = length(&r.direction);
+        let mut t = t_start;
+
+        // Rays scattered by the surface start on it: the side they leave to is given by their
+        // direction, and they step away before marching. Distances are measured from that side.
+        // A ray entering the bounding box right on the surface hits it there.
+        let distance = self.function.distance(&r.at(t));
+        let side = if distance.abs() >= HIT_DISTANCE || t_start > t_min {
+            distance.signum()
+        } else {
+            t += 10.0 * HIT_DISTANCE / ray_length;
+            self.normal(&r.at(t)).dot(&r.direction).signum()
+        };
+
+        for _ in 0..MAX_STEPS {
+            if t > t_end {
+                return None;
+            }
+            let distance = side * self.function.distance(&r.at(t));
+            if distance < HIT_DISTANCE {
+                let normal = self.normal(&r.at(t));
+                if normal.iter().any(|n| !n.is_finite()) {
+                    return None;
+                }
+                // there is no surface parametrization, only solid textures make sense
+                return Some(HitRecord::new(
+                    r,
+                    t,
+                    &normal,
+                    0.0,
+                    0.0,
+                    Arc::clone(&self.material),
+                ));
+            }
+            t += distance / ray_length;
+        }
+        None
+    }
+
+    fn bounding_box(&self, _t0: f32, _t1: f32) -> Option<AABB> {
+        Some(self.bounds)
+    }
+}
@@ -3,8 +3,9 @@ use crate::{
     aabb::AABB,
     collision::{HitRecord, Hittable},
 };
-use crate::{material::Material, ray::Ray};
-use nalgebra_glm::{dot, length2, Vec3};
+use crate::{material::Material, ray::Ray, utils::random_to_sphere};
+use nalgebra_glm::{dot, length2, normalize, Vec3};
+use std::f32::consts::PI;
 use std::sync::Arc;
 
 pub struct Sphere {
@@ -32,40 +33,7 @@ impl Sphere {
 
 impl Hittable for Sphere {
     fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
-        let oc = r.origin - self.center;
-        let a = length2(&r.direction);
-        let half_b = dot(&oc, &r.direction);
-        let c = length2(&oc) - self.radius * self.radius;
-        let discriminant = half_b * half_b - a * c;
-
-        if discriminant > 0f32 {
-            let root = f32::sqrt(discriminant);
-
-            let mut temp = (-half_b - root) / a;
-            if temp < t_max && temp > t_min {
-                let point = r.at(temp);
-                let outward_normal = (point - self.center) / self.radius;
-                return Some(HitRecord::new(
-                    r,
-                    temp,
-                    &outward_normal,
-                    Arc::clone(&self.material),
-                ));
-            }
-
-            temp = (-half_b + root) / a;
-            if temp < t_max && temp > t_min {
-                let point = r.at(temp);
-                let outward_normal = (point - self.center) / self.radius;
-                return Some(HitRecord::new(
-                    r,
-                    temp,
-                    &outward_normal,
-                    Arc::clone(&self.material),
-                ));
-            }
-        }
-        None
+        hit_sphere(&self.center, self.radius, &self.material, r, t_min, t_max)
     }
 
     fn bounding_box(&self, _t0: f32, _t1: f32) -> Option<crate::aabb::AABB> {
@@ -74,6 +42,108 @@ impl Hittable for Sphere {
             max: self.center + Vec3::new(self.radius, self.radius, self.radius),
         })
     }
+
+    // the sphere is sampled uniformly over the cone of directions it covers seen from origin
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> f32 {
+        let distance_squared = length2(&(self.center - origin));
+        if distance_squared <= self.radius * self.radius
+            || self
+                .hit(&Ray::new(*origin, *direction), 0.001, f32::INFINITY)
+                .is_none()
+        {
+            return 0.0;
+        }
+        let cos_theta_max = f32::sqrt(1.0 - self.radius * self.radius / distance_squared);
+        let solid_angle = 2.0 * PI * (1.0 - cos_theta_max);
+        1.0 / solid_angle
+    }
+
+    fn random(&self, origin: &Vec3) -> Option<Vec3> {
+        let direction = self.center - origin;
+        let distance_squared = length2(&direction);
+        if distance_squared <= self.radius * self.radius {
+            // origin is inside the sphere, the whole sphere of directions would have to be sampled
+            return None;
+        }
+        Some(random_to_sphere(
+            &normalize(&direction),
+            self.radius,
+            distance_squared,
+        ))
+    }
+}
+
+// intersection with the sphere of the given center and radius, shared with moving spheres
+pub(super) fn hit_sphere(
+    center: &Vec3,
+    radius: f32,
+    material: &Arc<Box<dyn Material>>,
+    r: &Ray,
+    t_min: f32,
+    t_max: f32,
+) -> Option<HitRecord> {
+    let oc = r.origin - center;
+    let a = length2(&r.direction);
+    let half_b = dot(&oc, &r.direction);
+    let c = length2(&oc) - radius * radius;
+    let discriminant = half_b * half_b - a * c;
+
+    if discriminant > 0f32 {
+        let root = f32::sqrt(discriminant);
+
+        let mut temp = (-half_b - root) / a;
+        if temp < t_max && temp > t_min {
+            let point = r.at(temp);
+            let outward_normal = (point - center) / radius;
+            let (u, v) = sphere_uv(&outward_normal);
+            let (dpdu, dpdv) = sphere_uv_derivatives(&outward_normal, radius);
+            return Some(
+                HitRecord::new(r, temp, &outward_normal, u, v, Arc::clone(material))
+                    .with_shading_frame(&outward_normal, &dpdu)
+                    .with_uv_derivatives(dpdu, dpdv),
+            );
+        }
+
+        temp = (-half_b + root) / a;
+        if temp < t_max && temp > t_min {
+            let point = r.at(temp);
+            let outward_normal = (point - center) / radius;
+            let (u, v) = sphere_uv(&outward_normal);
+            let (dpdu, dpdv) = sphere_uv_derivatives(&outward_normal, radius);
+            return Some(
+                HitRecord::new(r, temp, &outward_normal, u, v, Arc::clone(material))
+                    .with_shading_frame(&outward_normal, &dpdu)
+                    .with_uv_derivatives(dpdu, dpdv),
+            );
+        }
+    }
+    None
+}
+
+// spherical mapping of a point of the unit sphere: u goes around the y axis starting from -x,
+// v goes from the bottom pole (-y) to the top one
+fn sphere_uv(point: &Vec3) -> (f32, f32) {
+    let theta = f32::acos((-point.y).clamp(-1.0, 1.0));
+    let phi = f32::atan2(-point.z, point.x) + PI;
+    (phi / (2.0 * PI), theta / PI)
+}
+
+// derivatives of a point of the sphere of the given radius along u and v, from the point of the
+// unit sphere it is at, both are zero at the poles
+fn sphere_uv_derivatives(point: &Vec3, radius: f32) -> (Vec3, Vec3) {
+    let dpdu = 2.0 * PI * radius * Vec3::new(point.z, 0.0, -point.x);
+    let distance_to_axis = f32::hypot(point.x, point.z);
+    if distance_to_axis == 0.0 {
+        return (dpdu, Vec3::new(0.0, 0.0, 0.0));
+    }
+    let dpdv = PI
+        * radius
+        * Vec3::new(
+            -point.y * point.x / distance_to_axis,
+            distance_to_axis,
+            -point.y * point.z / distance_to_axis,
+        );
+    (dpdu, dpdv)
 }
 
 impl Position for Sphere {
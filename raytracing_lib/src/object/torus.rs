@@ -0,0 +1,199 @@
+use super::{axis_frame::AxisFrame, Position};
+use crate::{
+    aabb::AABB,
+    collision::{HitRecord, Hittable},
+};
+use crate::{material::Material, ray::Ray};
+use nalgebra_glm::{dot, length, normalize, Vec3};
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+// A ring around axis: the surface swept by a circle of radius minor_radius (the tube) whose
+// center goes around a circle of radius major_radius. u goes around the axis and v around the
+// tube, starting from its outer side.
+pub struct Torus {
+    frame: AxisFrame,
+    major_radius: f32,
+    minor_radius: f32,
+
+    material: Arc<Box<dyn Material>>,
+}
+
+impl std::fmt::Debug for Torus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format!(
+            "c:{:?} axis:{:?} R:{:?} r:{:?}",
+            self.frame.origin, self.frame.axis, self.major_radius, self.minor_radius
+        ))
+    }
+}
+
+impl Torus {
+    pub fn new(
+        center: Vec3,
+        axis: Vec3,
+        major_radius: f32,
+        minor_radius: f32,
+        material: Arc<Box<dyn Material>>,
+    ) -> Self {
+        Torus {
+            frame: AxisFrame::new(center, axis),
+            major_radius,
+            minor_radius,
+            material,
+        }
+    }
+}
+
+impl Hittable for Torus {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        let (origin, direction) = self.frame.ray_to_local(r);
+        let ray_length = length(&direction);
+        let direction = direction / ray_length;
+        // The quartic is solved from the point of the ray closest to the center, where its
+        // coefficients are small, which keeps far away rays accurate. The ray parameter there is
+        // a distance.
+        let shift = -dot(&origin, &direction);
+        let start = origin + shift * direction;
+        if length(&start) > self.major_radius + self.minor_radius {
+            return None;
+        }
+
+        let (o, d) = (start.map(f64::from), direction.map(f64::from));
+        let major2 = f64::from(self.major_radius).powi(2);
+        let minor2 = f64::from(self.minor_radius).powi(2);
+        let f = o.dot(&d);
+        let g = o.dot(&o) + major2 - minor2;
+        let coefficients = [
+            1.0,
+            4.0 * f,
+            4.0 * f * f + 2.0 * g - 4.0 * major2 * (d.x * d.x + d.z * d.z),
+            4.0 * f * g - 8.0 * major2 * (o.x * d.x + o.z * d.z),
+            g * g - 4.0 * major2 * (o.x * o.x + o.z * o.z),
+        ];
+
+        let t = solve_quartic(&coefficients)
+            .into_iter()
+            .map(|root| (root as f32 + shift) / ray_length)
+            .find(|&t| t < t_max && t > t_min)?;
+
+        let local = origin + t * ray_length * direction;
+        let distance_to_axis = f32::hypot(local.x, local.z);
+        if distance_to_axis == 0.0 {
+            return None;
+        }
+        let ring = Vec3::new(local.x, 0.0, local.z) * (self.major_radius / distance_to_axis);
+        let local_normal = normalize(&(local - ring));
+        let outward_normal = self.frame.direction_to_world(&local_normal);
+        let (u, tangent) = AxisFrame::azimuth(&local);
+        let dpdu = self.frame.direction_to_world(&(2.0 * PI * tangent));
+        // v turns around the tube, in the plane of the axis and the point
+        let ring_direction = ring / self.major_radius;
+        let dpdv = self.frame.direction_to_world(
+            &(-local_normal.y * ring_direction
+                + dot(&local_normal, &ring_direction) * Vec3::new(0.0, 1.0, 0.0)),
+        ) * (2.0 * PI * self.minor_radius);
+        let tube_angle = f32::atan2(local.y, distance_to_axis - self.major_radius);
+        let v = if tube_angle < 0.0 {
+            (tube_angle + 2.0 * PI) / (2.0 * PI)
+        } else {
+            tube_angle / (2.0 * PI)
+        };
+        Some(
+            HitRecord::new(r, t, &outward_normal, u, v, Arc::clone(&self.material))
+                .with_shading_frame(&outward_normal, &dpdu)
+                .with_uv_derivatives(dpdu, dpdv),
+        )
+    }
+
+    fn bounding_box(&self, _t0: f32, _t1: f32) -> Option<AABB> {
+        let ring = self.frame.circle_bounds(0.0, self.major_radius);
+        let tube = Vec3::new(self.minor_radius, self.minor_radius, self.minor_radius);
+        Some(AABB {
+            min: ring.min - tube,
+            max: ring.max + tube,
+        })
+    }
+}
+
+impl Position for Torus {
+    fn position(&self) -> &Vec3 {
+        &self.frame.origin
+    }
+}
+
+// real roots, in increasing order, of the polynomial with the given coefficients from the highest
+// degree down, by Ferrari's method. They are refined by Newton's method, the closed form loses
+// precision when roots are close.
+fn solve_quartic(coefficients: &[f64; 5]) -> Vec<f64> {
+    let [c4, c3, c2, c1, c0] = *coefficients;
+    let (a, b, c, d) = (c3 / c4, c2 / c4, c1 / c4, c0 / c4);
+    // depressed quartic y^4 + p y^2 + q y + r with x = y - a / 4
+    let p = b - 3.0 * a * a / 8.0;
+    let q = c - a * b / 2.0 + a * a * a / 8.0;
+    let r = d - a * c / 4.0 + a * a * b / 16.0 - 3.0 * a * a * a * a / 256.0;
+
+    let mut roots = Vec::with_capacity(4);
+    let resolvent = if q.abs() < 1e-12 {
+        0.0
+    } else {
+        largest_cubic_root(p, p * p / 4.0 - r, -q * q / 8.0)
+    };
+    if resolvent <= 0.0 {
+        // biquadratic, solved for y^2
+        for z in solve_quadratic(1.0, p, r) {
+            if z >= 0.0 {
+                roots.push(z.sqrt());
+                roots.push(-z.sqrt());
+            }
+        }
+    } else {
+        // y^4 + p y^2 + q y + r = (y^2 + p/2 + m)^2 - (s y - q / (2 s))^2 with s = sqrt(2 m)
+        let s = f64::sqrt(2.0 * resolvent);
+        roots.extend(solve_quadratic(
+            1.0,
+            -s,
+            p / 2.0 + resolvent + q / (2.0 * s),
+        ));
+        roots.extend(solve_quadratic(1.0, s, p / 2.0 + resolvent - q / (2.0 * s)));
+    }
+
+    for root in roots.iter_mut() {
+        *root -= a / 4.0;
+        for _ in 0..2 {
+            let value = (((*root + a) * *root + b) * *root + c) * *root + d;
+            let derivative = ((4.0 * *root + 3.0 * a) * *root + 2.0 * b) * *root + c;
+            if derivative != 0.0 {
+                *root -= value / derivative;
+            }
+        }
+    }
+    roots.sort_by(|x, y| x.partial_cmp(y).unwrap());
+    roots
+}
+
+fn solve_quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return vec![];
+    }
+    let root = discriminant.sqrt();
+    vec![(-b - root) / (2.0 * a), (-b + root) / (2.0 * a)]
+}
+
+// largest real root of x^3 + a x^2 + b x + c
+fn largest_cubic_root(a: f64, b: f64, c: f64) -> f64 {
+    // depressed cubic t^3 + p t + q with x = t - a / 3
+    let p = b - a * a / 3.0;
+    let q = 2.0 * a * a * a / 27.0 - a * b / 3.0 + c;
+    let discriminant = q * q / 4.0 + p * p * p / 27.0;
+    let t = if discriminant >= 0.0 {
+        let root = discriminant.sqrt();
+        f64::cbrt(-q / 2.0 + root) + f64::cbrt(-q / 2.0 - root)
+    } else {
+        // three real roots, p is negative
+        let angle = f64::acos((3.0 * q / (2.0 * p) * f64::sqrt(-3.0 / p)).clamp(-1.0, 1.0));
+        2.0 * f64::sqrt(-p / 3.0) * f64::cos(angle / 3.0)
+    };
+    t - a / 3.0
+}
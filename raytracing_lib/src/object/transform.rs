@@ -0,0 +1,140 @@
+use crate::{
+    aabb::AABB,
+    collision::{HitRecord, Hittable},
+    ray::Ray,
+};
+use nalgebra_glm::{length, mat4_to_mat3, normalize, vec4_to_vec3, Mat3, Mat4, Vec3, Vec4};
+use std::sync::Arc;
+
+// An instance of an object placed in the world by an affine transform (translation, rotation,
+// scale, shear). The object is shared, so many instances of a large mesh only cost a matrix each.
+// Rays are brought into the space of the object rather than the object into the world.
+pub struct Transform {
+    object: Arc<dyn Hittable>,
+    maps: AffineMaps,
+}
+
+// the maps between the space of an object and the world given by an affine matrix
+pub(super) struct AffineMaps {
+    matrix: Mat4,
+    inverse: Mat4,
+    // linear parts of the matrices, for directions, and the inverse transpose for normals
+    linear: Mat3,
+    inverse_linear: Mat3,
+    normal_matrix: Mat3,
+}
+
+impl std::fmt::Debug for Transform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format!("matrix:{:?}", self.maps.matrix))
+    }
+}
+
+impl Transform {
+    // the matrix goes from the space of the object to the world, it must be invertible
+    pub fn new(object: Arc<dyn Hittable>, matrix: Mat4) -> Result<Self, String> {
+        let inverse = matrix.try_inverse().ok_or_else(|| {
+            format!(
+                "Cannot instance an object with a singular transform {}",
+                matrix
+            )
+        })?;
+        Ok(Transform {
+            object,
+            maps: AffineMaps::new(matrix, inverse),
+        })
+    }
+}
+
+impl AffineMaps {
+    // the inverse must be the inverse of the matrix, it is given when it is known to be cheaper
+    pub(super) fn new(matrix: Mat4, inverse: Mat4) -> Self {
+        let inverse_linear = mat4_to_mat3(&inverse);
+        AffineMaps {
+            matrix,
+            inverse,
+            linear: mat4_to_mat3(&matrix),
+            inverse_linear,
+            normal_matrix: inverse_linear.transpose(),
+        }
+    }
+
+    fn ray_to_object(&self, r: &Ray) -> Ray {
+        // the direction is not normalized so that distances along the ray stay the same
+        Ray::with_time(
+            transform_point(&self.inverse, &r.origin),
+            self.inverse_linear * r.direction,
+            r.time,
+        )
+    }
+
+    pub(super) fn hit(
+        &self,
+        object: &dyn Hittable,
+        r: &Ray,
+        t_min: f32,
+        t_max: f32,
+    ) -> Option<HitRecord> {
+        let mut record = object.hit(&self.ray_to_object(r), t_min, t_max)?;
+        // the normal matrix keeps normals on the same side of the ray, front_face stays valid
+        record.point = r.at(record.t);
+        record.geometric_normal = normalize(&(self.normal_matrix * record.geometric_normal));
+        record.tangent = self.linear * record.tangent;
+        record.dpdu = self.linear * record.dpdu;
+        record.dpdv = self.linear * record.dpdv;
+        let normal = self.normal_matrix * record.normal;
+        record.set_shading_normal(&normal);
+        Some(record)
+    }
+
+    // box around the eight transformed corners of a box of the object
+    pub(super) fn bounding_box(&self, aabb: &AABB) -> AABB {
+        let mut min = Vec3::repeat(f32::INFINITY);
+        let mut max = Vec3::repeat(f32::NEG_INFINITY);
+        for i in 0..8 {
+            let corner = Vec3::new(
+                if i & 1 == 0 { aabb.min.x } else { aabb.max.x },
+                if i & 2 == 0 { aabb.min.y } else { aabb.max.y },
+                if i & 4 == 0 { aabb.min.z } else { aabb.max.z },
+            );
+            let corner = transform_point(&self.matrix, &corner);
+            min = min.inf(&corner);
+            max = max.sup(&corner);
+        }
+        AABB { min, max }
+    }
+}
+
+impl Hittable for Transform {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        self.maps.hit(self.object.as_ref(), r, t_min, t_max)
+    }
+
+    fn bounding_box(&self, t0: f32, t1: f32) -> Option<AABB> {
+        let aabb = self.object.bounding_box(t0, t1)?;
+        Some(self.maps.bounding_box(&aabb))
+    }
+
+    // directions are mapped to the object by the inverse linear part A and renormalized, which
+    // changes solid angles by |det A| / |A d|³ for a unit direction d
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> f32 {
+        let maps = &self.maps;
+        let object_origin = transform_point(&maps.inverse, origin);
+        let object_direction = maps.inverse_linear * normalize(direction);
+        let scale = length(&object_direction);
+        self.object.pdf_value(&object_origin, &object_direction)
+            * maps.inverse_linear.determinant().abs()
+            / (scale * scale * scale)
+    }
+
+    fn random(&self, origin: &Vec3) -> Option<Vec3> {
+        let object_origin = transform_point(&self.maps.inverse, origin);
+        self.object
+            .random(&object_origin)
+            .map(|direction| self.maps.linear * direction)
+    }
+}
+
+fn transform_point(matrix: &Mat4, point: &Vec3) -> Vec3 {
+    vec4_to_vec3(&(matrix * Vec4::new(point.x, point.y, point.z, 1.0)))
+}
@@ -0,0 +1,259 @@
+use super::mesh::MeshData;
+use crate::{
+    aabb::{AABB, AABB_PADDING},
+    collision::{HitRecord, Hittable},
+};
+use crate::{material::Material, ray::Ray};
+use nalgebra_glm::{cross, dot, length, length2, normalize, Vec2, Vec3};
+use std::sync::Arc;
+
+pub struct Triangle {
+    mesh: Arc<MeshData>,
+    index: usize,
+
+    material: Arc<Box<dyn Material>>,
+}
+
+impl std::fmt::Debug for Triangle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (v0, v1, v2) = self.vertices();
+        f.write_str(&format!("v0:{:?} v1:{:?} v2:{:?}", v0, v1, v2))
+    }
+}
+
+impl Triangle {
+    pub fn new(v0: Vec3, v1: Vec3, v2: Vec3, material: Arc<Box<dyn Material>>) -> Self {
+        let mesh = MeshData {
+            positions: vec![v0, v1, v2],
+            normals: None,
+            uvs: None,
+            colors: None,
+            indices: vec![[0, 1, 2]],
+        };
+        Triangle {
+            mesh: Arc::new(mesh),
+            index: 0,
+            material,
+        }
+    }
+
+    pub(super) fn from_mesh(
+        mesh: Arc<MeshData>,
+        index: usize,
+        material: Arc<Box<dyn Material>>,
+    ) -> Self {
+        Triangle {
+            mesh,
+            index,
+            material,
+        }
+    }
+
+    pub fn vertices(&self) -> (&Vec3, &Vec3, &Vec3) {
+        let [i0, i1, i2] = self.mesh.indices[self.index];
+        (
+            &self.mesh.positions[i0],
+            &self.mesh.positions[i1],
+            &self.mesh.positions[i2],
+        )
+    }
+
+    fn face_normal(&self) -> Vec3 {
+        let (v0, v1, v2) = self.vertices();
+        normalize(&cross(&(v1 - v0), &(v2 - v0)))
+    }
+
+    // interpolates the per-vertex normals if the mesh has some, falls back to the face normal
+    fn normal_at(&self, b0: f32, b1: f32, b2: f32) -> Vec3 {
+        match &self.mesh.normals {
+            Some(normals) => {
+                let [i0, i1, i2] = self.mesh.indices[self.index];
+                normalize(&(b0 * normals[i0] + b1 * normals[i1] + b2 * normals[i2]))
+            }
+            None => self.face_normal(),
+        }
+    }
+
+    fn vertex_uvs(&self) -> (Vec2, Vec2, Vec2) {
+        match &self.mesh.uvs {
+            Some(uvs) => {
+                let [i0, i1, i2] = self.mesh.indices[self.index];
+                (uvs[i0], uvs[i1], uvs[i2])
+            }
+            None => (
+                Vec2::new(0.0, 0.0),
+                Vec2::new(1.0, 0.0),
+                Vec2::new(0.0, 1.0),
+            ),
+        }
+    }
+
+    // derivatives of the point along u and v over the triangle, None for degenerate uvs
+    fn uv_derivatives(&self) -> Option<(Vec3, Vec3)> {
+        let (v0, v1, v2) = self.vertices();
+        let (uv0, uv1, uv2) = self.vertex_uvs();
+        let (dp1, dp2) = (v1 - v0, v2 - v0);
+        let (duv1, duv2) = (uv1 - uv0, uv2 - uv0);
+        let det = duv1.x * duv2.y - duv1.y * duv2.x;
+        if det.abs() < 1e-12 {
+            return None;
+        }
+        Some((
+            (dp1 * duv2.y - dp2 * duv1.y) / det,
+            (dp2 * duv1.x - dp1 * duv2.x) / det,
+        ))
+    }
+
+    // interpolates the per-vertex uvs if the mesh has some, falls back to the barycentric
+    // coordinates of the second and third vertices
+    fn uv_at(&self, b0: f32, b1: f32, b2: f32) -> Vec2 {
+        let (uv0, uv1, uv2) = self.vertex_uvs();
+        b0 * uv0 + b1 * uv1 + b2 * uv2
+    }
+}
+
+// index of the component of v with the largest absolute value
+#[inline]
+fn max_dimension(v: &Vec3) -> usize {
+    if v.x.abs() > v.y.abs() {
+        if v.x.abs() > v.z.abs() {
+            0
+        } else {
+            2
+        }
+    } else if v.y.abs() > v.z.abs() {
+        1
+    } else {
+        2
+    }
+}
+
+impl Hittable for Triangle {
+    // watertight ray/triangle intersection from Woop, Benthin and Wald (JCGT 2013):
+    // the triangle is sheared into a space where the ray goes along +z from the origin,
+    // which makes the edge tests consistent between triangles sharing an edge
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        let kz = max_dimension(&r.direction);
+        let mut kx = (kz + 1) % 3;
+        let mut ky = (kx + 1) % 3;
+        if r.direction[kz] < 0.0 {
+            std::mem::swap(&mut kx, &mut ky);
+        }
+
+        let sx = r.direction[kx] / r.direction[kz];
+        let sy = r.direction[ky] / r.direction[kz];
+        let sz = 1.0 / r.direction[kz];
+
+        let (v0, v1, v2) = self.vertices();
+        let a = v0 - r.origin;
+        let b = v1 - r.origin;
+        let c = v2 - r.origin;
+
+        let ax = a[kx] - sx * a[kz];
+        let ay = a[ky] - sy * a[kz];
+        let bx = b[kx] - sx * b[kz];
+        let by = b[ky] - sy * b[kz];
+        let cx = c[kx] - sx * c[kz];
+        let cy = c[ky] - sy * c[kz];
+
+        let mut u = cx * by - cy * bx;
+        let mut v = ax * cy - ay * cx;
+        let mut w = bx * ay - by * ax;
+
+        // the ray goes exactly through an edge, fall back to double precision
+        if u == 0.0 || v == 0.0 || w == 0.0 {
+            u = (cx as f64 * by as f64 - cy as f64 * bx as f64) as f32;
+            v = (ax as f64 * cy as f64 - ay as f64 * cx as f64) as f32;
+            w = (bx as f64 * ay as f64 - by as f64 * ax as f64) as f32;
+        }
+
+        if (u < 0.0 || v < 0.0 || w < 0.0) && (u > 0.0 || v > 0.0 || w > 0.0) {
+            return None;
+        }
+
+        let det = u + v + w;
+        if det == 0.0 {
+            return None;
+        }
+
+        let t_scaled = u * sz * a[kz] + v * sz * b[kz] + w * sz * c[kz];
+        let t = t_scaled / det;
+        if !(t < t_max && t > t_min) {
+            return None;
+        }
+
+        let (b0, b1, b2) = (u / det, v / det, w / det);
+        let shading_normal = self.normal_at(b0, b1, b2);
+        // the face normal is oriented like the vertex normals, whatever the winding of the triangle
+        let mut outward_normal = self.face_normal();
+        if dot(&outward_normal, &shading_normal) < 0.0 {
+            outward_normal = -outward_normal;
+        }
+        let uv = self.uv_at(b0, b1, b2);
+        let record = HitRecord::new(
+            r,
+            t,
+            &outward_normal,
+            uv.x,
+            uv.y,
+            Arc::clone(&self.material),
+        );
+        let record = match self.uv_derivatives() {
+            Some((dpdu, dpdv)) => record
+                .with_shading_frame(&shading_normal, &dpdu)
+                .with_uv_derivatives(dpdu, dpdv),
+            // the tangent falls back to the first edge
+            None => {
+                let (v0, v1, _) = self.vertices();
+                record.with_shading_frame(&shading_normal, &(v1 - v0))
+            }
+        };
+        Some(match &self.mesh.colors {
+            Some(colors) => {
+                let [i0, i1, i2] = self.mesh.indices[self.index];
+                record.with_vertex_color(b0 * colors[i0] + b1 * colors[i1] + b2 * colors[i2])
+            }
+            None => record,
+        })
+    }
+
+    fn bounding_box(&self, _t0: f32, _t1: f32) -> Option<AABB> {
+        let (v0, v1, v2) = self.vertices();
+        let min = Vec3::new(
+            f32::min(v0.x, f32::min(v1.x, v2.x)),
+            f32::min(v0.y, f32::min(v1.y, v2.y)),
+            f32::min(v0.z, f32::min(v1.z, v2.z)),
+        );
+        let max = Vec3::new(
+            f32::max(v0.x, f32::max(v1.x, v2.x)),
+            f32::max(v0.y, f32::max(v1.y, v2.y)),
+            f32::max(v0.z, f32::max(v1.z, v2.z)),
+        );
+        Some(AABB { min, max }.padded(AABB_PADDING))
+    }
+
+    // the triangle is sampled uniformly over its area, the density is converted to solid angle
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> f32 {
+        let record = match self.hit(&Ray::new(*origin, *direction), 0.001, f32::INFINITY) {
+            Some(record) => record,
+            None => return 0.0,
+        };
+        let (v0, v1, v2) = self.vertices();
+        let normal = cross(&(v1 - v0), &(v2 - v0));
+        let area = 0.5 * length(&normal);
+        let distance_squared = record.t * record.t * length2(direction);
+        let cosine = f32::abs(dot(direction, &normal)) / (length(direction) * 2.0 * area);
+        if cosine <= 0.0 {
+            return 0.0;
+        }
+        distance_squared / (cosine * area)
+    }
+
+    fn random(&self, origin: &Vec3) -> Option<Vec3> {
+        let (v0, v1, v2) = self.vertices();
+        let r1 = f32::sqrt(rand::random::<f32>());
+        let r2 = rand::random::<f32>();
+        let point = (1.0 - r1) * v0 + r1 * (1.0 - r2) * v1 + r1 * r2 * v2;
+        Some(point - origin)
+    }
+}
@@ -4,11 +4,21 @@ use nalgebra_glm::Vec3;
 pub struct Ray {
     pub origin: Vec3,
     pub direction: Vec3,
+    // instant at which the ray is cast, moving objects are hit where they are at that time
+    pub time: f32,
 }
 
 impl Ray {
     pub fn new(origin: Vec3, direction: Vec3) -> Self {
-        Ray { origin, direction }
+        Ray::with_time(origin, direction, 0.0)
+    }
+
+    pub fn with_time(origin: Vec3, direction: Vec3, time: f32) -> Self {
+        Ray {
+            origin,
+            direction,
+            time,
+        }
     }
 
     pub fn at(&self, t: f32) -> Vec3 {
@@ -8,9 +8,33 @@ use std::sync::RwLock;
 use std::{path::Path, sync::Arc, thread, thread::JoinHandle, time::Duration};
 use threadpool::ThreadPool;
 
-use crate::{bvh::BVHNode, collision::Hittable, export::PPMWriter};
+use crate::background::{Background, Gradient};
+use crate::collision::{HitRecord, Hittable, HittableList};
+use crate::export::PPMWriter;
+use crate::light::Light;
 use crate::{Camera, Canvas, Ray, World};
 
+// how light found by sampling lights and light found by following scattered rays are weighted
+// against each other (multiple importance sampling), see Veach's thesis chapter 9
+#[derive(Debug, Clone, Copy)]
+pub enum MisHeuristic {
+    Balance,
+    Power,
+}
+
+impl MisHeuristic {
+    // weight of a sample drawn with density pdf when the other strategy draws it with density other_pdf
+    fn weight(&self, pdf: f32, other_pdf: f32) -> f32 {
+        if other_pdf <= 0.0 {
+            return 1.0;
+        }
+        match self {
+            MisHeuristic::Balance => pdf / (pdf + other_pdf),
+            MisHeuristic::Power => pdf * pdf / (pdf * pdf + other_pdf * other_pdf),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct RenderPass {
     pub canvas: Canvas,
@@ -21,6 +45,8 @@ pub struct RenderPass {
 pub struct Renderer {
     world: World,
     camera: Arc<Camera>,
+    background: Arc<dyn Background>,
+    mis_heuristic: MisHeuristic,
     width: usize,
     height: usize,
     samples: usize,
@@ -34,6 +60,8 @@ impl Renderer {
         Self {
             world,
             camera,
+            background: Arc::new(Gradient::default()),
+            mis_heuristic: MisHeuristic::Power,
             width: 960,
             height: 540,
             samples: 100,
@@ -63,6 +91,16 @@ impl Renderer {
         self
     }
 
+    pub fn background(mut self, background: Arc<dyn Background>) -> Self {
+        self.background = background;
+        self
+    }
+
+    pub fn mis_heuristic(mut self, mis_heuristic: MisHeuristic) -> Self {
+        self.mis_heuristic = mis_heuristic;
+        self
+    }
+
     pub fn get_render_pass_rx(&mut self) -> Receiver<RenderPass> {
         let (tx, rx) = unbounded::<RenderPass>();
         self.render_pass_tx = Some(tx);
@@ -124,7 +162,9 @@ impl Renderer {
             let height = self.height;
             let width = self.width;
             let camera_arc = Arc::clone(&self.camera);
-            let hittables_arc = self.world.get_hittables();
+            let world = self.world.clone();
+            let background_arc = Arc::clone(&self.background);
+            let mis_heuristic = self.mis_heuristic;
             let tx_clone = progress_tracker.as_mut().map(|x| x.0.clone());
             let data_tx_clone = data_tx.clone();
             let should_stop_arc = Arc::clone(&should_stop_clone);
@@ -133,8 +173,15 @@ impl Renderer {
                 if *should_stop_arc.read().unwrap() {
                     return;
                 }
-                let result =
-                    Renderer::compute_render(height, width, camera_arc, hittables_arc, bounces);
+                let result = Renderer::compute_render(
+                    height,
+                    width,
+                    camera_arc,
+                    world,
+                    background_arc,
+                    mis_heuristic,
+                    bounces,
+                );
                 if let Some(tx) = tx_clone {
                     tx.send(()).unwrap();
                 }
@@ -158,7 +205,9 @@ impl Renderer {
         canvas_height: usize,
         canvas_width: usize,
         camera: Arc<Camera>,
-        world: Arc<BVHNode>,
+        world: World,
+        background: Arc<dyn Background>,
+        mis_heuristic: MisHeuristic,
         max_depth: usize,
     ) -> Canvas {
         let mut temp_cv = Canvas::new_initialized(canvas_height, canvas_width);
@@ -172,32 +221,160 @@ impl Renderer {
                 temp_cv.set_pixel(
                     i,
                     canvas_height - 1 - j,
-                    Renderer::ray_color(&world, &r, max_depth),
+                    Renderer::ray_color(&world, background.as_ref(), mis_heuristic, &r, max_depth),
                 )
             }
         }
         temp_cv
     }
 
-    fn ray_color(world: &Arc<BVHNode>, r: &Ray, depth: usize) -> Vec3 {
-        if depth == 0 {
-            return Vec3::new(0.0, 0.0, 0.0);
+    fn ray_color(
+        world: &World,
+        background: &dyn Background,
+        heuristic: MisHeuristic,
+        r: &Ray,
+        max_depth: usize,
+    ) -> Vec3 {
+        let emitters = world.get_emitters();
+        let lights = world.get_lights();
+        let mut color = Vec3::new(0.0, 0.0, 0.0);
+        // fraction of the light arriving along the current ray that makes it to the camera
+        let mut throughput = Vec3::new(1.0, 1.0, 1.0);
+        let mut ray = Ray::with_time(r.origin, r.direction, r.time);
+        // origin and density of the current ray if it was scattered by a non specular material,
+        // in which case the light it finds could also have been found by sampling lights
+        let mut previous_bounce: Option<(Vec3, f32)> = None;
+
+        for _ in 0..max_depth {
+            // the ray can be scattered by a medium before it reaches the next surface
+            let surface_record = world.hit(&ray, 0.001f32, f32::INFINITY);
+            let t_surface = surface_record
+                .as_ref()
+                .map_or(f32::INFINITY, |record| record.t);
+            let record = world
+                .media_hit(&ray, 0.001f32, t_surface)
+                .or(surface_record);
+            let mut record = match record {
+                Some(record) => record,
+                None => {
+                    let weight = match previous_bounce {
+                        Some((_, scattering_pdf)) => {
+                            heuristic.weight(scattering_pdf, background.pdf(&ray.direction))
+                        }
+                        None => {
+                            // distant lights like the sun disk are only visible to camera rays
+                            // and specular bounces, the others sampled them already
+                            for light in lights.iter() {
+                                color += throughput.component_mul(&light.emitted(&ray.direction));
+                            }
+                            1.0
+                        }
+                    };
+                    color += throughput.component_mul(&background.color(&ray)) * weight;
+                    break;
+                }
+            };
+
+            let material = Arc::clone(&record.material_hit);
+            material.perturb_normal(&mut record);
+            let emitted = material.emitted(&record);
+            if emitted != Vec3::new(0.0, 0.0, 0.0) {
+                let weight = match previous_bounce {
+                    Some((origin, scattering_pdf)) => heuristic
+                        .weight(scattering_pdf, emitters.pdf_value(&origin, &ray.direction)),
+                    None => 1.0,
+                };
+                color += throughput.component_mul(&emitted) * weight;
+            }
+
+            let scattered = match material.scatter(&ray, &record) {
+                Some(scattered) => scattered,
+                // no scattered ray, only the emitted light remains
+                None => break,
+            };
+
+            previous_bounce = None;
+            if !material.is_specular() {
+                let direct_light = Renderer::sample_lights(
+                    world, &emitters, &lights, background, heuristic, &ray, &record,
+                );
+                color += throughput.component_mul(&direct_light);
+                let scattering_pdf = material.scattering_pdf(&ray, &record, &scattered.direction);
+                previous_bounce = Some((record.point, scattering_pdf));
+            }
+            throughput = throughput.component_mul(&material.weight(&ray, &record, &scattered));
+            ray = scattered;
+        }
+        color
+    }
+
+    // light reaching a non specular surface directly, estimated with one shadow ray toward a
+    // random emitter, one toward each light and one toward the background
+    fn sample_lights(
+        world: &World,
+        emitters: &HittableList,
+        lights: &[Box<dyn Light>],
+        background: &dyn Background,
+        heuristic: MisHeuristic,
+        ray_in: &Ray,
+        record: &HitRecord,
+    ) -> Vec3 {
+        let material = &record.material_hit;
+        let mut light = Vec3::new(0.0, 0.0, 0.0);
+
+        if let Some(direction) = emitters.random(&record.point) {
+            let light_pdf = emitters.pdf_value(&record.point, &direction);
+            let scattering = material.eval(ray_in, record, &direction);
+            if light_pdf > 0.0 && scattering != Vec3::new(0.0, 0.0, 0.0) {
+                // whatever the shadow ray hits first is what the surface sees in this direction
+                let shadow_ray = Ray::with_time(record.point, direction, ray_in.time);
+                if let Some(light_record) = world.hit(&shadow_ray, 0.001f32, f32::INFINITY) {
+                    let emitted = light_record.material_hit.emitted(&light_record)
+                        * world.transmittance(&shadow_ray, 0.001f32, light_record.t);
+                    let weight = heuristic.weight(
+                        light_pdf,
+                        material.scattering_pdf(ray_in, record, &direction),
+                    );
+                    light += scattering.component_mul(&emitted) * weight / light_pdf;
+                }
+            }
         }
 
-        if let Some(record) = world.hit(r, 0.001f32, f32::INFINITY) {
-            if let Some(scattered) = record.material_hit.scatter(r, &record) {
-                // a ray is scattered by the material
-                let albedo = record.material_hit.albedo();
+        // lights cannot be hit by scattered rays, there is no need for weighting
+        for light_sample in lights
+            .iter()
+            .filter_map(|light| light.sample(&record.point))
+        {
+            let scattering = material.eval(ray_in, record, &light_sample.direction);
+            if scattering != Vec3::new(0.0, 0.0, 0.0) {
+                let shadow_ray = Ray::with_time(record.point, light_sample.direction, ray_in.time);
+                let distance = light_sample.distance - 0.001f32;
+                if world.hit(&shadow_ray, 0.001f32, distance).is_none() {
+                    light += scattering.component_mul(&light_sample.radiance)
+                        * world.transmittance(&shadow_ray, 0.001f32, distance);
+                }
+            }
+        }
 
-                return albedo.component_mul(&Renderer::ray_color(world, &scattered, depth - 1));
+        if let Some((direction, light_pdf)) = background.sample() {
+            let scattering = material.eval(ray_in, record, &direction);
+            if light_pdf > 0.0 && scattering != Vec3::new(0.0, 0.0, 0.0) {
+                let shadow_ray = Ray::with_time(record.point, direction, ray_in.time);
+                if world.hit(&shadow_ray, 0.001f32, f32::INFINITY).is_none() {
+                    let weight = heuristic.weight(
+                        light_pdf,
+                        material.scattering_pdf(ray_in, record, &direction),
+                    );
+                    let transmittance = world.transmittance(&shadow_ray, 0.001f32, f32::INFINITY);
+                    light += scattering.component_mul(&background.color(&shadow_ray))
+                        * weight
+                        * transmittance
+                        / light_pdf;
+                }
             }
-            // no scattered ray, return black
-            Vec3::new(0.0, 0.0, 0.0)
-        } else {
-            let unit_direction = nalgebra_glm::normalize(&r.direction);
-            let t = 0.5 * (unit_direction.y + 1.0); // t is between 0.0 and 1.0
-            nalgebra_glm::lerp(&Vec3::new(1.0, 1.0, 1.0), &Vec3::new(0.5, 0.7, 1.0), t)
         }
+
+        light
     }
 
     fn start_progress_tracker(&self, length: u64) -> Option<(Sender<()>, JoinHandle<()>)> {
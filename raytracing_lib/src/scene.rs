@@ -1,12 +1,31 @@
 pub mod serialization {
     use serde::Deserialize;
-    use std::{collections::HashMap, convert::TryFrom};
+    use std::{
+        collections::{HashMap, HashSet},
+        convert::TryFrom,
+        path::{Path, PathBuf},
+        sync::Arc,
+    };
 
-    use nalgebra_glm::Vec3;
+    use nalgebra_glm::{Mat4, Vec3};
 
     use crate::{
-        material::{Dielectric, Diffuse, Metal},
-        object::Sphere,
+        background::{self, EnvironmentMap, Gradient, NoBackground, PreethamSky, SolidColor},
+        collision::{Hittable, HittableList},
+        import::{load_obj_meshes, load_ply, load_stl},
+        light::{DirectionalLight, PointLight, SpotLight},
+        material::{
+            self, Dielectric, Diffuse, DiffuseLight, Hair, HenyeyGreenstein, Isotropic, Metal,
+            MetallicRoughness,
+        },
+        object::{
+            self, Cone, ConstantMedium, Csg, Cuboid, Curve, Cylinder, Disk, Heightfield,
+            KeyframedTransform, MovingSphere, Plane, Quad, SignedDistanceField, Sphere, Torus,
+            Transform,
+        },
+        sdf,
+        texture::{self, Checker, ColorRamp, Constant, ImageTexture, NoiseTexture},
+        volume::{DensityGrid, HeterogeneousMedium, NoiseDensity},
         MaterialAtlas, World,
     };
 
@@ -32,56 +51,1273 @@ pub mod serialization {
     pub struct Object {
         pub object_id: String,
         pub geometry: Geometry,
+        #[serde(default = "default_material")]
         pub material: String,
+        // steps applied in order to the geometry
+        #[serde(default)]
+        pub transform: Vec<TransformStep>,
+        // poses of the object over time, applied after the transform
+        #[serde(default)]
+        pub keyframes: Vec<Keyframe>,
+        // turns the geometry into the boundary of a fog of this density, the geometry must be
+        // closed and convex and the material is its phase function (see Material::Isotropic)
+        pub density: Option<f32>,
+    }
+
+    #[derive(Deserialize)]
+    pub struct Keyframe {
+        pub time: f32,
+        pub translation: Option<Point>,
+        // angle in degrees, counterclockwise around the axis
+        pub rotation: Option<Rotation>,
+        pub scale: Option<Point>,
+    }
+
+    #[derive(Deserialize)]
+    pub struct Rotation {
+        pub axis: Point,
+        pub angle: f32,
+    }
+
+    impl From<Keyframe> for object::Keyframe {
+        fn from(other: Keyframe) -> object::Keyframe {
+            let mut keyframe = object::Keyframe::new(other.time);
+            if let Some(translation) = other.translation {
+                keyframe = keyframe.translation(translation.into());
+            }
+            if let Some(Rotation { axis, angle }) = other.rotation {
+                keyframe = keyframe.rotation(axis.into(), angle);
+            }
+            if let Some(scale) = other.scale {
+                keyframe = keyframe.scale(scale.into());
+            }
+            keyframe
+        }
+    }
+
+    #[derive(Deserialize)]
+    pub enum TransformStep {
+        Translate(Point),
+        // angle in degrees, counterclockwise around the axis
+        Rotate { axis: Point, angle: f32 },
+        Scale(Point),
+    }
+
+    impl From<TransformStep> for Mat4 {
+        fn from(other: TransformStep) -> Mat4 {
+            match other {
+                TransformStep::Translate(offset) => nalgebra_glm::translation(&offset.into()),
+                TransformStep::Rotate { axis, angle } => {
+                    nalgebra_glm::rotation(angle.to_radians(), &axis.into())
+                }
+                TransformStep::Scale(factors) => nalgebra_glm::scaling(&factors.into()),
+            }
+        }
     }
 
     #[derive(Deserialize)]
     pub enum Geometry {
-        Sphere { center: Point, radius: f32 },
+        Sphere {
+            center: Point,
+            radius: f32,
+        },
+        // sphere going in a straight line from center0 at time0 to center1 at time1
+        MovingSphere {
+            center0: Point,
+            center1: Point,
+            radius: f32,
+            #[serde(default)]
+            time0: f32,
+            #[serde(default = "default_time1")]
+            time1: f32,
+        },
+        // infinite plane going through point
+        Plane {
+            point: Point,
+            normal: Point,
+        },
+        // parallelogram with a corner at `corner` and sides u and v, facing cross(u, v)
+        Quad {
+            corner: Point,
+            u: Point,
+            v: Point,
+        },
+        // axis aligned box between two opposite corners
+        Cuboid {
+            min: Point,
+            max: Point,
+        },
+        // flat disk facing normal
+        Disk {
+            center: Point,
+            normal: Point,
+            radius: f32,
+        },
+        // closed cylinder between the centers of its caps
+        Cylinder {
+            base: Point,
+            top: Point,
+            radius: f32,
+        },
+        // closed cone from the center of its base to its apex, truncated when top_radius is set
+        Cone {
+            base: Point,
+            top: Point,
+            radius: f32,
+            #[serde(default)]
+            top_radius: f32,
+        },
+        // ring around axis, the tube of radius minor_radius goes along a circle of major_radius
+        Torus {
+            center: Point,
+            axis: Point,
+            major_radius: f32,
+            minor_radius: f32,
+        },
+        // path to a Wavefront OBJ file relative to the scene file, the object material is used for
+        // faces without MTL material
+        Obj {
+            path: String,
+        },
+        // path to a PLY file relative to the scene file, ASCII or binary, its vertex colors tint the
+        // object material
+        Ply {
+            path: String,
+        },
+        // path to an STL file relative to the scene file, ASCII or binary
+        Stl {
+            path: String,
+        },
+        // terrain over the box from corner spanning size, heights of 0 and 1 are at its bottom and top
+        Heightfield {
+            heights: Heights,
+            corner: Point,
+            size: Point,
+        },
+        // smooth strand through the points, like a hair or a blade of grass, whose width goes
+        // from width at the first point to tip_width (width by default) at the last one
+        Curve {
+            points: Vec<Point>,
+            width: f32,
+            tip_width: Option<f32>,
+            mode: CurveMode,
+        },
+        // shares the geometry and materials of a previous object, its material is ignored
+        Instance {
+            object: String,
+        },
+        // surface of a distance function, traced inside the box between min and max
+        Sdf {
+            shape: Shape,
+            min: Point,
+            max: Point,
+        },
+        // solid combining two closed geometries
+        Csg {
+            operation: CsgOperation,
+            left: Box<Operand>,
+            right: Box<Operand>,
+        },
     }
 
+    #[derive(Deserialize)]
+    pub enum Heights {
+        // grayscale image relative to the scene file, see Heightfield::from_image
+        Image { path: String },
+        // text file relative to the scene file with the grid size followed by the values, see
+        // Heightfield::from_file
+        Grid { path: String },
+    }
+
+    #[derive(Deserialize)]
+    pub enum CurveMode {
+        Ribbon,
+        Cylinder,
+    }
+
+    impl From<CurveMode> for object::CurveMode {
+        fn from(other: CurveMode) -> object::CurveMode {
+            match other {
+                CurveMode::Ribbon => object::CurveMode::Ribbon,
+                CurveMode::Cylinder => object::CurveMode::Cylinder,
+            }
+        }
+    }
+
+    #[derive(Deserialize)]
+    pub enum CsgOperation {
+        Union,
+        Intersection,
+        // the left operand minus the right one
+        Difference,
+    }
+
+    impl From<CsgOperation> for object::CsgOperation {
+        fn from(other: CsgOperation) -> object::CsgOperation {
+            match other {
+                CsgOperation::Union => object::CsgOperation::Union,
+                CsgOperation::Intersection => object::CsgOperation::Intersection,
+                CsgOperation::Difference => object::CsgOperation::Difference,
+            }
+        }
+    }
+
+    // distance function of an Sdf geometry, see the sdf module
+    #[derive(Deserialize)]
+    pub enum Shape {
+        Sphere {
+            center: Point,
+            radius: f32,
+        },
+        // ring around the y axis
+        Torus {
+            center: Point,
+            major_radius: f32,
+            minor_radius: f32,
+        },
+        RoundedBox {
+            center: Point,
+            half_size: Point,
+            radius: f32,
+        },
+        Capsule {
+            a: Point,
+            b: Point,
+            radius: f32,
+        },
+        Union {
+            left: Box<Shape>,
+            right: Box<Shape>,
+        },
+        SmoothUnion {
+            left: Box<Shape>,
+            right: Box<Shape>,
+            smoothness: f32,
+        },
+        // morph from left to right as the factor goes from 0 to 1
+        Blend {
+            left: Box<Shape>,
+            right: Box<Shape>,
+            factor: f32,
+        },
+    }
+
+    impl From<Shape> for Box<dyn sdf::DistanceFunction> {
+        fn from(other: Shape) -> Box<dyn sdf::DistanceFunction> {
+            let shape = |shape: Box<Shape>| Box::<dyn sdf::DistanceFunction>::from(*shape);
+            match other {
+                Shape::Sphere { center, radius } => Box::new(sdf::Sphere {
+                    center: center.into(),
+                    radius,
+                }),
+                Shape::Torus {
+                    center,
+                    major_radius,
+                    minor_radius,
+                } => Box::new(sdf::Torus {
+                    center: center.into(),
+                    major_radius,
+                    minor_radius,
+                }),
+                Shape::RoundedBox {
+                    center,
+                    half_size,
+                    radius,
+                } => Box::new(sdf::RoundedBox {
+                    center: center.into(),
+                    half_size: half_size.into(),
+                    radius,
+                }),
+                Shape::Capsule { a, b, radius } => Box::new(sdf::Capsule {
+                    a: a.into(),
+                    b: b.into(),
+                    radius,
+                }),
+                Shape::Union { left, right } => Box::new(sdf::Union {
+                    left: shape(left),
+                    right: shape(right),
+                }),
+                Shape::SmoothUnion {
+                    left,
+                    right,
+                    smoothness,
+                } => Box::new(sdf::SmoothUnion {
+                    left: shape(left),
+                    right: shape(right),
+                    smoothness,
+                }),
+                Shape::Blend {
+                    left,
+                    right,
+                    factor,
+                } => Box::new(sdf::Blend {
+                    left: shape(left),
+                    right: shape(right),
+                    factor,
+                }),
+            }
+        }
+    }
+
+    // operand of a CSG geometry, with the material of the object unless another one is given
+    #[derive(Deserialize)]
+    pub struct Operand {
+        pub geometry: Geometry,
+        pub material: Option<String>,
+        #[serde(default)]
+        pub transform: Vec<TransformStep>,
+    }
+
+    #[derive(Deserialize)]
+    pub enum Texture {
+        Constant {
+            color: Color3,
+        },
+        // solid checker board made of cubes of size `scale`
+        Checker {
+            even: Color3,
+            odd: Color3,
+            #[serde(default = "default_checker_scale")]
+            scale: f32,
+        },
+        // Perlin noise pattern, the ramp maps its values in [0, 1] to colors and defaults to a
+        // preset matching the pattern
+        Noise {
+            pattern: NoisePattern,
+            #[serde(default)]
+            seed: u64,
+            #[serde(default = "default_noise_scale")]
+            scale: f32,
+            #[serde(default = "default_octaves")]
+            octaves: usize,
+            ramp: Option<Vec<(f32, Color3)>>,
+        },
+        // PNG, JPEG or HDR image relative to the scene file, PNG and JPEG colors are read as sRGB
+        // unless srgb is false, which is needed for normal maps
+        Image {
+            path: String,
+            #[serde(default = "default_srgb")]
+            srgb: bool,
+            #[serde(default = "default_wrap")]
+            wrap: Wrap,
+            #[serde(default = "default_filter")]
+            filter: Filter,
+        },
+    }
+
+    #[derive(Deserialize)]
+    pub enum NoisePattern {
+        Fbm,
+        Turbulence,
+        Marble,
+        Wood,
+    }
+
+    impl From<NoisePattern> for texture::NoisePattern {
+        fn from(other: NoisePattern) -> texture::NoisePattern {
+            match other {
+                NoisePattern::Fbm => texture::NoisePattern::Fbm,
+                NoisePattern::Turbulence => texture::NoisePattern::Turbulence,
+                NoisePattern::Marble => texture::NoisePattern::Marble,
+                NoisePattern::Wood => texture::NoisePattern::Wood,
+            }
+        }
+    }
+
+    #[derive(Deserialize)]
+    pub enum Wrap {
+        Repeat,
+        Clamp,
+        Mirror,
+    }
+
+    impl From<Wrap> for texture::Wrap {
+        fn from(other: Wrap) -> texture::Wrap {
+            match other {
+                Wrap::Repeat => texture::Wrap::Repeat,
+                Wrap::Clamp => texture::Wrap::Clamp,
+                Wrap::Mirror => texture::Wrap::Mirror,
+            }
+        }
+    }
+
+    #[derive(Deserialize)]
+    pub enum Filter {
+        Nearest,
+        Bilinear,
+    }
+
+    impl From<Filter> for texture::Filter {
+        fn from(other: Filter) -> texture::Filter {
+            match other {
+                Filter::Nearest => texture::Filter::Nearest,
+                Filter::Bilinear => texture::Filter::Bilinear,
+            }
+        }
+    }
+
+    // the albedo of Diffuse and Metal, and the emission of DiffuseLight, are either a constant
+    // color, the name of a texture or the path of an image (albedo_map) relative to the scene file
+    // used with the default wrapping and filtering
     #[derive(Deserialize)]
     pub enum Material {
-        Dielectric { refractive_index: f32 },
-        Diffuse { albedo: Point },
-        Metal { albedo: Point, fuziness: f32 },
+        Dielectric {
+            refractive_index: f32,
+            #[serde(flatten)]
+            normal_perturbation: NormalPerturbation,
+        },
+        Diffuse {
+            albedo: Option<Point>,
+            texture: Option<String>,
+            albedo_map: Option<String>,
+            #[serde(flatten)]
+            normal_perturbation: NormalPerturbation,
+        },
+        DiffuseLight {
+            emit: Option<Color3>,
+            texture: Option<String>,
+        },
+        // phase function of volumes, scattering light equally in all directions
+        Isotropic {
+            albedo: Option<Color3>,
+            texture: Option<String>,
+        },
+        // phase function of volumes, g goes from -1 (backward scattering) to 1 (forward)
+        HenyeyGreenstein {
+            albedo: Option<Color3>,
+            texture: Option<String>,
+            g: f32,
+        },
+        // fibers of hair or fur, color is the fraction of the light kept when going once through
+        // a fiber, roughness spreads the highlights along it and azimuthal_roughness the light
+        // going through it
+        Hair {
+            color: Option<Color3>,
+            texture: Option<String>,
+            roughness: f32,
+            azimuthal_roughness: f32,
+            refraction_index: Option<f32>,
+            // tilt of the scales of the cuticle in degrees
+            scale_angle: Option<f32>,
+        },
+        Metal {
+            albedo: Option<Point>,
+            texture: Option<String>,
+            albedo_map: Option<String>,
+            fuziness: f32,
+            #[serde(flatten)]
+            normal_perturbation: NormalPerturbation,
+        },
+        // physically based material of glTF, its metallic_roughness texture scales roughness by
+        // its green channel and metallic by its blue one
+        MetallicRoughness {
+            base_color: Option<Color3>,
+            texture: Option<String>,
+            base_color_map: Option<String>,
+            metallic: f32,
+            roughness: f32,
+            metallic_roughness: Option<String>,
+            emissive: Option<Color3>,
+            #[serde(flatten)]
+            normal_perturbation: NormalPerturbation,
+        },
+    }
+
+    // optional normal map or bump map of a material, given as texture names
+    #[derive(Deserialize)]
+    pub struct NormalPerturbation {
+        normal_map: Option<String>,
+        bump_map: Option<String>,
+        // height in world units of the white parts of the bump map
+        #[serde(default = "default_bump_strength")]
+        bump_strength: f32,
+    }
+
+    #[derive(Deserialize)]
+    pub enum Light {
+        Point {
+            position: Point,
+            intensity: Color3,
+        },
+        // angles are the half apertures of the cones, in degrees
+        Spot {
+            position: Point,
+            direction: Point,
+            intensity: Color3,
+            inner_angle: f32,
+            outer_angle: f32,
+        },
+        // direction in which the light travels
+        Directional {
+            direction: Point,
+            irradiance: Color3,
+        },
+    }
+
+    #[derive(Deserialize)]
+    pub enum Background {
+        None,
+        Solid {
+            color: Color3,
+        },
+        Gradient {
+            bottom: Color3,
+            top: Color3,
+            #[serde(default = "default_up")]
+            up: Point,
+        },
+        // equirectangular Radiance HDR map relative to the scene file, rotated around the y axis by
+        // `rotation` degrees
+        EnvironmentMap {
+            path: String,
+            #[serde(default)]
+            rotation: f32,
+            #[serde(default = "default_intensity")]
+            intensity: f32,
+        },
+        // analytic daylight sky, turbidity goes from 2 (clear) to 10 (hazy). Unless `sun` is
+        // false, the matching sun disk is added to the lights of the scene
+        Sky {
+            sun_direction: Point,
+            #[serde(default = "default_turbidity")]
+            turbidity: f32,
+            #[serde(default = "default_intensity")]
+            intensity: f32,
+            #[serde(default = "default_sun")]
+            sun: bool,
+        },
+    }
+
+    fn default_material() -> String {
+        String::from("Default")
+    }
+
+    fn default_time1() -> f32 {
+        1.0
+    }
+
+    fn default_density_scale() -> f32 {
+        1.0
+    }
+
+    fn default_up() -> Point {
+        Point(0.0, 1.0, 0.0)
+    }
+
+    fn default_intensity() -> f32 {
+        1.0
+    }
+
+    fn default_checker_scale() -> f32 {
+        1.0
+    }
+
+    fn default_noise_scale() -> f32 {
+        1.0
+    }
+
+    fn default_octaves() -> usize {
+        7
+    }
+
+    fn default_srgb() -> bool {
+        true
+    }
+
+    fn default_bump_strength() -> f32 {
+        1.0
+    }
+
+    fn default_wrap() -> Wrap {
+        Wrap::Repeat
+    }
+
+    fn default_filter() -> Filter {
+        Filter::Bilinear
+    }
+
+    fn default_turbidity() -> f32 {
+        3.0
+    }
+
+    fn default_sun() -> bool {
+        true
+    }
+
+    impl Default for Background {
+        fn default() -> Self {
+            Background::Gradient {
+                bottom: Color3(1.0, 1.0, 1.0),
+                top: Color3(0.5, 0.7, 1.0),
+                up: default_up(),
+            }
+        }
     }
 
     #[derive(Deserialize)]
     pub struct Scene {
         objects: Vec<Object>,
+        #[serde(default)]
+        textures: HashMap<String, Texture>,
         materials: HashMap<String, Material>,
+        #[serde(default)]
+        lights: Vec<Light>,
+        #[serde(default)]
+        background: Background,
+        #[serde(default)]
+        volumes: Vec<Volume>,
+        // relative paths are resolved from there, the scene file directory when loaded from a file
+        #[serde(skip)]
+        directory: PathBuf,
+    }
+
+    // medium of varying density filling a box, the material is its phase function
+    #[derive(Deserialize)]
+    pub struct Volume {
+        min: Point,
+        max: Point,
+        density: Density,
+        #[serde(default = "default_density_scale")]
+        density_scale: f32,
+        material: String,
+    }
+
+    #[derive(Deserialize)]
+    pub enum Density {
+        // text file relative to the scene file with the grid size followed by the values, see
+        // DensityGrid::from_file
+        Grid {
+            path: String,
+        },
+        // cloud-like Perlin noise, unset parameters keep the defaults of NoiseDensity
+        Noise {
+            #[serde(default)]
+            seed: u64,
+            scale: Option<f32>,
+            octaves: Option<usize>,
+            coverage: Option<f32>,
+        },
+    }
+
+    impl Scene {
+        // reads a YAML scene file, the paths it contains are relative to its directory
+        pub fn from_file<P: AsRef<Path>>(path: &P) -> Result<Self, String> {
+            let path = path.as_ref();
+            let content = std::fs::read_to_string(path)
+                .map_err(|err| format!("Cannot load scene {}: {}", path.display(), err))?;
+            let mut scene: Scene = serde_yaml::from_str(&content)
+                .map_err(|err| format!("Scene {}: {}", path.display(), err))?;
+            scene.directory = path.parent().map(Path::to_path_buf).unwrap_or_default();
+            Ok(scene)
+        }
+
+        // the background is a renderer setting, it has to be extracted before converting the scene to a World
+        pub fn background(&self) -> Result<Arc<dyn background::Background>, String> {
+            Ok(match &self.background {
+                Background::None => Arc::new(NoBackground),
+                Background::Solid { color } => {
+                    Arc::new(SolidColor::new(Vec3::new(color.0, color.1, color.2)))
+                }
+                Background::Gradient { bottom, top, up } => Arc::new(Gradient::new(
+                    Vec3::new(bottom.0, bottom.1, bottom.2),
+                    Vec3::new(top.0, top.1, top.2),
+                    Vec3::new(up.0, up.1, up.2),
+                )),
+                Background::EnvironmentMap {
+                    path,
+                    rotation,
+                    intensity,
+                } => Arc::new(
+                    EnvironmentMap::from_file(&self.directory.join(path))?
+                        .rotation(*rotation)
+                        .intensity(*intensity),
+                ),
+                Background::Sky {
+                    sun_direction,
+                    turbidity,
+                    intensity,
+                    ..
+                } => Arc::new(
+                    PreethamSky::new(
+                        Vec3::new(sun_direction.0, sun_direction.1, sun_direction.2),
+                        *turbidity,
+                    )
+                    .intensity(*intensity),
+                ),
+            })
+        }
+    }
+
+    fn color_texture(
+        color: Option<Vec3>,
+        texture: Option<String>,
+        image_path: Option<PathBuf>,
+        textures: &HashMap<String, Arc<dyn texture::Texture>>,
+    ) -> Result<Arc<dyn texture::Texture>, String> {
+        match (color, texture, image_path) {
+            (Some(color), None, None) => Ok(Arc::new(Constant::new(color))),
+            (None, Some(name), None) => textures
+                .get(&name)
+                .map(Arc::clone)
+                .ok_or_else(|| format!("Cannot find texture {}", name)),
+            (None, None, Some(path)) => Ok(Arc::new(ImageTexture::from_file(&path)?)),
+            _ => {
+                Err("Exactly one of a color, a texture name or an image path must be given".into())
+            }
+        }
+    }
+
+    fn normal_perturbation(
+        normal_perturbation: NormalPerturbation,
+        textures: &HashMap<String, Arc<dyn texture::Texture>>,
+    ) -> Result<Option<material::NormalPerturbation>, String> {
+        let texture = |name: &String| {
+            textures
+                .get(name)
+                .map(Arc::clone)
+                .ok_or_else(|| format!("Cannot find texture {}", name))
+        };
+        match (normal_perturbation.normal_map, normal_perturbation.bump_map) {
+            (None, None) => Ok(None),
+            (Some(name), None) => Ok(Some(material::NormalPerturbation::NormalMap(texture(
+                &name,
+            )?))),
+            (None, Some(name)) => Ok(Some(material::NormalPerturbation::BumpMap {
+                height: texture(&name)?,
+                strength: normal_perturbation.bump_strength,
+            })),
+            _ => Err("A material cannot have both a normal map and a bump map".into()),
+        }
+    }
+
+    // parts of a geometry, and whether they can be sampled as emitters
+    type Parts = (Vec<Arc<dyn Hittable>>, bool);
+
+    fn transform_matrix(steps: Vec<TransformStep>) -> Mat4 {
+        steps
+            .into_iter()
+            .fold(Mat4::identity(), |matrix, step| Mat4::from(step) * matrix)
+    }
+
+    // builds the parts of a geometry before its transform
+    fn build_geometry(
+        geometry: Geometry,
+        material: Arc<Box<dyn material::Material>>,
+        is_emissive: bool,
+        atlas: &mut MaterialAtlas,
+        geometries: &HashMap<String, Parts>,
+        directory: &Path,
+    ) -> Result<Parts, String> {
+        Ok(match geometry {
+            Geometry::Sphere { center, radius } => (
+                vec![Arc::new(Sphere::new(center.into(), radius, material))],
+                is_emissive,
+            ),
+            Geometry::MovingSphere {
+                center0,
+                center1,
+                radius,
+                time0,
+                time1,
+            } => (
+                vec![Arc::new(MovingSphere::new(
+                    center0.into(),
+                    center1.into(),
+                    time0,
+                    time1,
+                    radius,
+                    material,
+                ))],
+                false,
+            ),
+            Geometry::Plane { point, normal } => (
+                vec![Arc::new(Plane::new(point.into(), normal.into(), material))],
+                false,
+            ),
+            Geometry::Quad { corner, u, v } => (
+                vec![Arc::new(Quad::new(
+                    corner.into(),
+                    u.into(),
+                    v.into(),
+                    material,
+                ))],
+                is_emissive,
+            ),
+            Geometry::Cuboid { min, max } => (
+                vec![Arc::new(Cuboid::new(min.into(), max.into(), material))],
+                false,
+            ),
+            Geometry::Disk {
+                center,
+                normal,
+                radius,
+            } => (
+                vec![Arc::new(Disk::new(
+                    center.into(),
+                    normal.into(),
+                    radius,
+                    material,
+                ))],
+                is_emissive,
+            ),
+            Geometry::Cylinder { base, top, radius } => (
+                vec![Arc::new(Cylinder::new(
+                    base.into(),
+                    top.into(),
+                    radius,
+                    material,
+                ))],
+                false,
+            ),
+            Geometry::Cone {
+                base,
+                top,
+                radius,
+                top_radius,
+            } => (
+                vec![Arc::new(Cone::truncated(
+                    base.into(),
+                    top.into(),
+                    radius,
+                    top_radius,
+                    material,
+                ))],
+                false,
+            ),
+            Geometry::Torus {
+                center,
+                axis,
+                major_radius,
+                minor_radius,
+            } => (
+                vec![Arc::new(Torus::new(
+                    center.into(),
+                    axis.into(),
+                    major_radius,
+                    minor_radius,
+                    material,
+                ))],
+                false,
+            ),
+            Geometry::Obj { path } => (
+                load_obj_meshes(&directory.join(path), atlas, material)?
+                    .into_iter()
+                    .map(|mesh| Arc::new(mesh) as Arc<dyn Hittable>)
+                    .collect(),
+                is_emissive,
+            ),
+            Geometry::Ply { path } => (
+                vec![Arc::new(load_ply(&directory.join(path), material)?)],
+                is_emissive,
+            ),
+            Geometry::Stl { path } => (
+                vec![Arc::new(load_stl(&directory.join(path), material)?)],
+                is_emissive,
+            ),
+            Geometry::Heightfield {
+                heights,
+                corner,
+                size,
+            } => {
+                let (corner, size) = (corner.into(), size.into());
+                let heightfield = match heights {
+                    Heights::Image { path } => {
+                        Heightfield::from_image(&directory.join(path), corner, size, material)?
+                    }
+                    Heights::Grid { path } => {
+                        Heightfield::from_file(&directory.join(path), corner, size, material)?
+                    }
+                };
+                (vec![Arc::new(heightfield)], false)
+            }
+            Geometry::Curve {
+                points,
+                width,
+                tip_width,
+                mode,
+            } => (
+                Curve::strand(
+                    &points.into_iter().map(Vec3::from).collect::<Vec<_>>(),
+                    width,
+                    tip_width.unwrap_or(width),
+                    mode.into(),
+                    material,
+                )?
+                .into_iter()
+                .map(|curve| Arc::new(curve) as Arc<dyn Hittable>)
+                .collect(),
+                false,
+            ),
+            Geometry::Instance { object } => geometries
+                .get(&object)
+                .cloned()
+                .ok_or_else(|| format!("Cannot find object {} to instance", object))?,
+            Geometry::Sdf { shape, min, max } => (
+                vec![Arc::new(SignedDistanceField::new(
+                    Box::<dyn sdf::DistanceFunction>::from(shape),
+                    min.into(),
+                    max.into(),
+                    material,
+                ))],
+                false,
+            ),
+            Geometry::Csg {
+                operation,
+                left,
+                right,
+            } => {
+                let left = build_operand(*left, &material, atlas, geometries, directory)?;
+                let right = build_operand(*right, &material, atlas, geometries, directory)?;
+                (
+                    vec![Arc::new(Csg::new(operation.into(), left, right))],
+                    false,
+                )
+            }
+        })
+    }
+
+    fn build_operand(
+        operand: Operand,
+        material: &Arc<Box<dyn material::Material>>,
+        atlas: &mut MaterialAtlas,
+        geometries: &HashMap<String, Parts>,
+        directory: &Path,
+    ) -> Result<Arc<dyn Hittable>, String> {
+        let Operand {
+            geometry,
+            material: material_name,
+            transform,
+        } = operand;
+        let material = match material_name {
+            Some(name) => atlas
+                .get_material(&name)
+                .ok_or_else(|| format!("Cannot find material {}", name))?,
+            None => Arc::clone(material),
+        };
+        let (mut parts, _) =
+            build_geometry(geometry, material, false, atlas, geometries, directory)?;
+        let operand: Arc<dyn Hittable> = if parts.len() == 1 {
+            parts.pop().unwrap()
+        } else {
+            let mut list = HittableList::new();
+            for part in parts.into_iter() {
+                list.add_hittable(part);
+            }
+            Arc::new(list)
+        };
+        let matrix = transform_matrix(transform);
+        if matrix == Mat4::identity() {
+            Ok(operand)
+        } else {
+            Ok(Arc::new(Transform::new(operand, matrix)?))
+        }
     }
 
     impl TryFrom<Scene> for (MaterialAtlas, World) {
         type Error = String;
         fn try_from(scene: Scene) -> Result<Self, Self::Error> {
+            let directory = scene.directory.as_path();
+            let mut textures: HashMap<String, Arc<dyn texture::Texture>> = HashMap::new();
+            for (name, texture) in scene.textures.into_iter() {
+                let texture: Arc<dyn texture::Texture> = match texture {
+                    Texture::Constant { color } => Arc::new(Constant::new(color.into())),
+                    Texture::Checker { even, odd, scale } => {
+                        Arc::new(Checker::from_colors(even.into(), odd.into(), scale))
+                    }
+                    Texture::Noise {
+                        pattern,
+                        seed,
+                        scale,
+                        octaves,
+                        ramp,
+                    } => {
+                        let mut noise = NoiseTexture::new(pattern.into(), seed)
+                            .scale(scale)
+                            .octaves(octaves);
+                        if let Some(ramp) = ramp {
+                            let stops = ramp
+                                .into_iter()
+                                .map(|(position, color)| (position, color.into()))
+                                .collect();
+                            noise = noise.ramp(
+                                ColorRamp::new(stops)
+                                    .map_err(|err| format!("Texture {}: {}", name, err))?,
+                            );
+                        }
+                        Arc::new(noise)
+                    }
+                    Texture::Image {
+                        path,
+                        srgb,
+                        wrap,
+                        filter,
+                    } => {
+                        let path = directory.join(path);
+                        let image = if srgb {
+                            ImageTexture::from_file(&path)?
+                        } else {
+                            ImageTexture::from_file_linear(&path)?
+                        };
+                        Arc::new(image.wrap(wrap.into()).filter(filter.into()))
+                    }
+                };
+                textures.insert(name, texture);
+            }
+
             let mut atlas = MaterialAtlas::default();
+            // objects using these materials are registered as emitters so that they get sampled
+            let mut emissive_materials = HashSet::new();
             for (name, material) in scene.materials.into_iter() {
+                if let Material::DiffuseLight { .. } = material {
+                    emissive_materials.insert(name.clone());
+                }
+                let in_material = |err: String| format!("Material {}: {}", name, err);
                 match material {
-                    Material::Dielectric { refractive_index } => {
-                        atlas.insert_material(&name, Dielectric::new(refractive_index))
+                    Material::Dielectric {
+                        refractive_index,
+                        normal_perturbation: perturbation,
+                    } => {
+                        let mut dielectric = Dielectric::new(refractive_index);
+                        dielectric.normal_perturbation =
+                            normal_perturbation(perturbation, &textures).map_err(in_material)?;
+                        atlas.insert_material(&name, dielectric)
+                    }
+                    Material::Diffuse {
+                        albedo,
+                        texture,
+                        albedo_map,
+                        normal_perturbation: perturbation,
+                    } => {
+                        let albedo = color_texture(
+                            albedo.map(Vec3::from),
+                            texture,
+                            albedo_map.map(|path| directory.join(path)),
+                            &textures,
+                        )
+                        .map_err(in_material)?;
+                        let mut diffuse = Diffuse::with_texture(albedo);
+                        diffuse.normal_perturbation =
+                            normal_perturbation(perturbation, &textures).map_err(in_material)?;
+                        atlas.insert_material(&name, diffuse)
+                    }
+                    Material::DiffuseLight { emit, texture } => {
+                        let emit = color_texture(emit.map(Vec3::from), texture, None, &textures)
+                            .map_err(in_material)?;
+                        atlas.insert_material(&name, DiffuseLight::with_texture(emit))
+                    }
+                    Material::Isotropic { albedo, texture } => {
+                        let albedo =
+                            color_texture(albedo.map(Vec3::from), texture, None, &textures)
+                                .map_err(in_material)?;
+                        atlas.insert_material(&name, Isotropic::with_texture(albedo))
                     }
-                    Material::Diffuse { albedo } => {
-                        atlas.insert_material(&name, Diffuse::new(albedo.into()))
+                    Material::HenyeyGreenstein { albedo, texture, g } => {
+                        let albedo =
+                            color_texture(albedo.map(Vec3::from), texture, None, &textures)
+                                .map_err(in_material)?;
+                        atlas.insert_material(&name, HenyeyGreenstein::with_texture(albedo, g))
                     }
-                    Material::Metal { albedo, fuziness } => {
-                        atlas.insert_material(&name, Metal::new(albedo.into(), fuziness))
+                    Material::Hair {
+                        color,
+                        texture,
+                        roughness,
+                        azimuthal_roughness,
+                        refraction_index,
+                        scale_angle,
+                    } => {
+                        let color = color_texture(color.map(Vec3::from), texture, None, &textures)
+                            .map_err(in_material)?;
+                        let mut hair = Hair::with_texture(color, roughness, azimuthal_roughness);
+                        if let Some(refraction_index) = refraction_index {
+                            hair = hair.refraction_index(refraction_index);
+                        }
+                        if let Some(scale_angle) = scale_angle {
+                            hair = hair.scale_angle(scale_angle);
+                        }
+                        atlas.insert_material(&name, hair)
+                    }
+                    Material::Metal {
+                        albedo,
+                        texture,
+                        albedo_map,
+                        fuziness,
+                        normal_perturbation: perturbation,
+                    } => {
+                        let albedo = color_texture(
+                            albedo.map(Vec3::from),
+                            texture,
+                            albedo_map.map(|path| directory.join(path)),
+                            &textures,
+                        )
+                        .map_err(in_material)?;
+                        let mut metal = Metal::with_texture(albedo, fuziness);
+                        metal.normal_perturbation =
+                            normal_perturbation(perturbation, &textures).map_err(in_material)?;
+                        atlas.insert_material(&name, metal)
+                    }
+                    Material::MetallicRoughness {
+                        base_color,
+                        texture,
+                        base_color_map,
+                        metallic,
+                        roughness,
+                        metallic_roughness,
+                        emissive,
+                        normal_perturbation: perturbation,
+                    } => {
+                        let base_color = color_texture(
+                            base_color.map(Vec3::from),
+                            texture,
+                            base_color_map.map(|path| directory.join(path)),
+                            &textures,
+                        )
+                        .map_err(in_material)?;
+                        let mut material =
+                            MetallicRoughness::with_texture(base_color, metallic, roughness);
+                        if let Some(texture) = metallic_roughness {
+                            material.metallic_roughness = Some(
+                                color_texture(None, Some(texture), None, &textures)
+                                    .map_err(in_material)?,
+                            );
+                        }
+                        if let Some(emissive) = emissive {
+                            material.emissive = Some(Arc::new(Constant::new(emissive.into())));
+                        }
+                        material.normal_perturbation =
+                            normal_perturbation(perturbation, &textures).map_err(in_material)?;
+                        atlas.insert_material(&name, material)
                     }
                 };
             }
             let mut world_builder = World::builder();
+            // geometry of each object before its transform, shared with its instances, and
+            // whether it is sampled as an emitter
+            let mut geometries: HashMap<String, Parts> = HashMap::new();
             for object in scene.objects.into_iter() {
-                let material = &object.material;
-                match object.geometry {
-                    Geometry::Sphere { center, radius } => world_builder.add_object(Sphere::new(
-                        center.into(),
-                        radius,
-                        atlas
-                            .get_material(&object.material)
-                            .ok_or_else(|| format!("Cannot find material {}", material))?,
+                let material = atlas
+                    .get_material(&object.material)
+                    .ok_or_else(|| format!("Cannot find material {}", object.material))?;
+                let object_id = object.object_id;
+                // the material of a volume is the phase function of its medium
+                let medium = object
+                    .density
+                    .map(|density| (density, Arc::clone(&material)));
+                let in_object = |err: String| format!("Object {}: {}", object_id, err);
+                let is_emissive = emissive_materials.contains(&object.material);
+                let (parts, is_emitter) = build_geometry(
+                    object.geometry,
+                    material,
+                    is_emissive,
+                    &mut atlas,
+                    &geometries,
+                    directory,
+                )
+                .map_err(in_object)?;
+
+                let matrix = transform_matrix(object.transform);
+                let keyframes: Vec<object::Keyframe> =
+                    object.keyframes.into_iter().map(Keyframe::into).collect();
+                for part in parts.iter() {
+                    let mut part: Arc<dyn Hittable> = if matrix == Mat4::identity() {
+                        Arc::clone(part)
+                    } else {
+                        Arc::new(Transform::new(Arc::clone(part), matrix).map_err(in_object)?)
+                    };
+                    if !keyframes.is_empty() {
+                        part = Arc::new(
+                            KeyframedTransform::new(part, keyframes.clone()).map_err(in_object)?,
+                        );
+                    }
+                    // the density is given in world units, the medium goes around the transform
+                    if let Some((density, phase_function)) = &medium {
+                        part = Arc::new(ConstantMedium::new(
+                            part,
+                            *density,
+                            Arc::clone(phase_function),
+                        ));
+                    }
+                    // moving emitters cannot be sampled, nor can volumes
+                    if is_emitter && keyframes.is_empty() && medium.is_none() {
+                        world_builder.add_shared_emitter(part);
+                    } else {
+                        world_builder.add_shared_object(part);
+                    }
+                }
+                geometries.insert(object_id, (parts, is_emitter));
+            }
+
+            for light in scene.lights.into_iter() {
+                match light {
+                    Light::Point {
+                        position,
+                        intensity,
+                    } => {
+                        world_builder.add_light(PointLight::new(position.into(), intensity.into()))
+                    }
+                    Light::Spot {
+                        position,
+                        direction,
+                        intensity,
+                        inner_angle,
+                        outer_angle,
+                    } => world_builder.add_light(SpotLight::new(
+                        position.into(),
+                        direction.into(),
+                        intensity.into(),
+                        inner_angle,
+                        outer_angle,
                     )),
+                    Light::Directional {
+                        direction,
+                        irradiance,
+                    } => world_builder
+                        .add_light(DirectionalLight::new(direction.into(), irradiance.into())),
+                };
+            }
+
+            for volume in scene.volumes.into_iter() {
+                let phase_function = atlas
+                    .get_material(&volume.material)
+                    .ok_or_else(|| format!("Cannot find material {}", volume.material))?;
+                let (min, max) = (volume.min.into(), volume.max.into());
+                let medium = match volume.density {
+                    Density::Grid { path } => HeterogeneousMedium::new(
+                        min,
+                        max,
+                        DensityGrid::from_file(&directory.join(path))?,
+                        phase_function,
+                    ),
+                    Density::Noise {
+                        seed,
+                        scale,
+                        octaves,
+                        coverage,
+                    } => {
+                        let mut density = NoiseDensity::new(seed);
+                        if let Some(scale) = scale {
+                            density = density.scale(scale);
+                        }
+                        if let Some(octaves) = octaves {
+                            density = density.octaves(octaves);
+                        }
+                        if let Some(coverage) = coverage {
+                            density = density.coverage(coverage);
+                        }
+                        HeterogeneousMedium::new(min, max, density, phase_function)
+                    }
                 };
+                world_builder.add_medium(medium.density_scale(volume.density_scale));
+            }
+
+            if let Background::Sky {
+                sun_direction,
+                turbidity,
+                intensity,
+                sun: true,
+            } = scene.background
+            {
+                let sky = PreethamSky::new(sun_direction.into(), turbidity).intensity(intensity);
+                world_builder.add_light(sky.sun_light());
             }
 
             Ok((atlas, world_builder.build()))
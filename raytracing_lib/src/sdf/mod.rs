@@ -0,0 +1,25 @@
+use nalgebra_glm::Vec3;
+
+mod operators;
+mod primitives;
+
+pub use operators::{Blend, SmoothUnion, Union};
+pub use primitives::{Capsule, RoundedBox, Sphere, Torus};
+
+// Signed distance to a surface: positive outside, negative inside. Sphere tracing steps along
+// rays by this distance, so it must never be larger than the actual distance to the surface.
+pub trait DistanceFunction: Send + Sync {
+    fn distance(&self, point: &Vec3) -> f32;
+}
+
+impl<F: Fn(&Vec3) -> f32 + Send + Sync> DistanceFunction for F {
+    fn distance(&self, point: &Vec3) -> f32 {
+        self(point)
+    }
+}
+
+impl DistanceFunction for Box<dyn DistanceFunction> {
+    fn distance(&self, point: &Vec3) -> f32 {
+        self.as_ref().distance(point)
+    }
+}
@@ -0,0 +1,47 @@
+use super::DistanceFunction;
+use nalgebra_glm::Vec3;
+
+pub struct Union<A: DistanceFunction, B: DistanceFunction> {
+    pub left: A,
+    pub right: B,
+}
+
+impl<A: DistanceFunction, B: DistanceFunction> DistanceFunction for Union<A, B> {
+    fn distance(&self, point: &Vec3) -> f32 {
+        f32::min(self.left.distance(point), self.right.distance(point))
+    }
+}
+
+// union melting both shapes together where they are closer than the smoothness (polynomial
+// smooth minimum)
+pub struct SmoothUnion<A: DistanceFunction, B: DistanceFunction> {
+    pub left: A,
+    pub right: B,
+    pub smoothness: f32,
+}
+
+impl<A: DistanceFunction, B: DistanceFunction> DistanceFunction for SmoothUnion<A, B> {
+    fn distance(&self, point: &Vec3) -> f32 {
+        let (a, b) = (self.left.distance(point), self.right.distance(point));
+        let k = self.smoothness;
+        if k <= 0.0 {
+            return f32::min(a, b);
+        }
+        let h = f32::max(k - (a - b).abs(), 0.0) / k;
+        f32::min(a, b) - h * h * k / 4.0
+    }
+}
+
+// morphs the left shape into the right one as the factor goes from 0 to 1
+pub struct Blend<A: DistanceFunction, B: DistanceFunction> {
+    pub left: A,
+    pub right: B,
+    pub factor: f32,
+}
+
+impl<A: DistanceFunction, B: DistanceFunction> DistanceFunction for Blend<A, B> {
+    fn distance(&self, point: &Vec3) -> f32 {
+        let (a, b) = (self.left.distance(point), self.right.distance(point));
+        a + self.factor * (b - a)
+    }
+}
@@ -0,0 +1,66 @@
+use super::DistanceFunction;
+use nalgebra_glm::{clamp_scalar, dot, length, Vec2, Vec3};
+
+// distance functions from Inigo Quilez, https://iquilezles.org/articles/distfunctions/
+
+pub struct Sphere {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+impl DistanceFunction for Sphere {
+    fn distance(&self, point: &Vec3) -> f32 {
+        length(&(point - self.center)) - self.radius
+    }
+}
+
+// ring around the y axis going through center, the tube has the minor radius
+pub struct Torus {
+    pub center: Vec3,
+    pub major_radius: f32,
+    pub minor_radius: f32,
+}
+
+impl DistanceFunction for Torus {
+    fn distance(&self, point: &Vec3) -> f32 {
+        let p = point - self.center;
+        let q = Vec2::new(length(&Vec2::new(p.x, p.z)) - self.major_radius, p.y);
+        length(&q) - self.minor_radius
+    }
+}
+
+// axis aligned box with its edges rounded by radius, it stays within the half size
+pub struct RoundedBox {
+    pub center: Vec3,
+    pub half_size: Vec3,
+    pub radius: f32,
+}
+
+impl DistanceFunction for RoundedBox {
+    fn distance(&self, point: &Vec3) -> f32 {
+        let radius = self.radius.min(self.half_size.min());
+        let q = (point - self.center).abs() - self.half_size + Vec3::repeat(radius);
+        length(&q.sup(&Vec3::repeat(0.0))) + f32::min(q.max(), 0.0) - radius
+    }
+}
+
+// points within radius of the segment from a to b
+pub struct Capsule {
+    pub a: Vec3,
+    pub b: Vec3,
+    pub radius: f32,
+}
+
+impl DistanceFunction for Capsule {
+    fn distance(&self, point: &Vec3) -> f32 {
+        let pa = point - self.a;
+        let ba = self.b - self.a;
+        let length2 = dot(&ba, &ba);
+        let h = if length2 > 0.0 {
+            clamp_scalar(dot(&pa, &ba) / length2, 0.0, 1.0)
+        } else {
+            0.0
+        };
+        length(&(pa - h * ba)) - self.radius
+    }
+}
@@ -0,0 +1,35 @@
+use super::{Constant, Texture};
+use nalgebra_glm::Vec3;
+use std::sync::Arc;
+
+// a solid 3D checker board made of cubes of the given size, alternating between two textures
+pub struct Checker {
+    even: Arc<dyn Texture>,
+    odd: Arc<dyn Texture>,
+    scale: f32,
+}
+
+impl Checker {
+    pub fn new(even: Arc<dyn Texture>, odd: Arc<dyn Texture>, scale: f32) -> Self {
+        Checker { even, odd, scale }
+    }
+
+    pub fn from_colors(even: Vec3, odd: Vec3, scale: f32) -> Self {
+        Checker::new(
+            Arc::new(Constant::new(even)),
+            Arc::new(Constant::new(odd)),
+            scale,
+        )
+    }
+}
+
+impl Texture for Checker {
+    fn value(&self, u: f32, v: f32, point: &Vec3) -> Vec3 {
+        let cell = (point / self.scale).map(|c| c.floor() as i64);
+        if (cell.x + cell.y + cell.z).rem_euclid(2) == 0 {
+            self.even.value(u, v, point)
+        } else {
+            self.odd.value(u, v, point)
+        }
+    }
+}
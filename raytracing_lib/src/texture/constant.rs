@@ -0,0 +1,19 @@
+use super::Texture;
+use nalgebra_glm::Vec3;
+
+pub struct Constant {
+    pub color: Vec3,
+}
+
+impl Constant {
+    pub fn new(color: Vec3) -> Self {
+        Constant { color }
+    }
+}
+
+impl Texture for Constant {
+    #[allow(unused_variables)]
+    fn value(&self, u: f32, v: f32, point: &Vec3) -> Vec3 {
+        self.color
+    }
+}
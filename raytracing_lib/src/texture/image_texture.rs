@@ -0,0 +1,155 @@
+use super::Texture;
+use crate::utils::srgb_to_linear;
+use image::DynamicImage;
+use nalgebra_glm::Vec3;
+use std::path::Path;
+
+// how texture coordinates outside of [0, 1] are brought back onto the image
+#[derive(Debug, Clone, Copy)]
+pub enum Wrap {
+    Repeat,
+    Clamp,
+    Mirror,
+}
+
+impl Wrap {
+    // brings a pixel index back into [0, size)
+    fn apply(&self, index: i64, size: usize) -> usize {
+        let size = size as i64;
+        let index = match self {
+            Wrap::Repeat => index.rem_euclid(size),
+            Wrap::Clamp => index.clamp(0, size - 1),
+            Wrap::Mirror => {
+                let index = index.rem_euclid(2 * size);
+                if index < size {
+                    index
+                } else {
+                    2 * size - 1 - index
+                }
+            }
+        };
+        index as usize
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Filter {
+    Nearest,
+    Bilinear,
+}
+
+// a texture backed by a buffer of linear colors, stored row by row from the top of the image:
+// v = 0 is the bottom row and v = 1 the top one
+pub struct ImageTexture {
+    width: usize,
+    height: usize,
+    data: Vec<Vec3>,
+    wrap: Wrap,
+    filter: Filter,
+}
+
+impl ImageTexture {
+    pub fn new(width: usize, height: usize, data: Vec<Vec3>) -> Result<Self, String> {
+        if width == 0 || height == 0 || data.len() != width * height {
+            return Err(format!(
+                "Image texture of {}x{} pixels needs {} values, got {}",
+                width,
+                height,
+                width * height,
+                data.len()
+            ));
+        }
+        Ok(ImageTexture {
+            width,
+            height,
+            data,
+            wrap: Wrap::Repeat,
+            filter: Filter::Bilinear,
+        })
+    }
+
+    // loads a PNG, JPEG or Radiance HDR file, 8 and 16 bits images are assumed to be sRGB encoded
+    // and are converted to linear colors
+    pub fn from_file<P: AsRef<Path>>(path: &P) -> Result<Self, String> {
+        ImageTexture::load(path.as_ref(), true)
+    }
+
+    // loads an image holding data rather than colors, like a normal map, without any conversion
+    pub fn from_file_linear<P: AsRef<Path>>(path: &P) -> Result<Self, String> {
+        ImageTexture::load(path.as_ref(), false)
+    }
+
+    // decodes an image file already in memory, like one embedded in a glTF file, srgb tells
+    // whether 8 and 16 bits images hold sRGB colors
+    pub fn from_memory(bytes: &[u8], srgb: bool) -> Result<Self, String> {
+        let image = image::load_from_memory(bytes)
+            .map_err(|err| format!("Cannot decode texture: {}", err))?;
+        ImageTexture::from_image(image, srgb)
+    }
+
+    fn load(path: &Path, srgb: bool) -> Result<Self, String> {
+        let image = image::open(path)
+            .map_err(|err| format!("Cannot load texture {}: {}", path.display(), err))?;
+        ImageTexture::from_image(image, srgb)
+    }
+
+    fn from_image(image: DynamicImage, srgb: bool) -> Result<Self, String> {
+        let is_linear = !srgb
+            || matches!(
+                image,
+                DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_)
+            );
+        let image = image.into_rgb32f();
+        let (width, height) = (image.width() as usize, image.height() as usize);
+        let data = image
+            .pixels()
+            .map(|p| {
+                let color = Vec3::new(p[0], p[1], p[2]);
+                if is_linear {
+                    color
+                } else {
+                    color.map(srgb_to_linear)
+                }
+            })
+            .collect();
+        ImageTexture::new(width, height, data)
+    }
+
+    pub fn wrap(mut self, wrap: Wrap) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    fn texel(&self, x: i64, y: i64) -> Vec3 {
+        let x = self.wrap.apply(x, self.width);
+        let y = self.wrap.apply(y, self.height);
+        self.data[y * self.width + x]
+    }
+}
+
+impl Texture for ImageTexture {
+    #[allow(unused_variables)]
+    fn value(&self, u: f32, v: f32, point: &Vec3) -> Vec3 {
+        // continuous pixel coordinates, y going down from the top row
+        let x = u * self.width as f32;
+        let y = (1.0 - v) * self.height as f32;
+        match self.filter {
+            Filter::Nearest => self.texel(x.floor() as i64, y.floor() as i64),
+            Filter::Bilinear => {
+                // pixel centers are at half integer coordinates
+                let (x, y) = (x - 0.5, y - 0.5);
+                let (x0, y0) = (x.floor(), y.floor());
+                let (fx, fy) = (x - x0, y - y0);
+                let (x0, y0) = (x0 as i64, y0 as i64);
+                let top = (1.0 - fx) * self.texel(x0, y0) + fx * self.texel(x0 + 1, y0);
+                let bottom = (1.0 - fx) * self.texel(x0, y0 + 1) + fx * self.texel(x0 + 1, y0 + 1);
+                (1.0 - fy) * top + fy * bottom
+            }
+        }
+    }
+}
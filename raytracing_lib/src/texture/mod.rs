@@ -0,0 +1,19 @@
+use nalgebra_glm::Vec3;
+
+mod checker;
+mod constant;
+mod image_texture;
+mod noise;
+mod perlin;
+
+pub use checker::Checker;
+pub use constant::Constant;
+pub use image_texture::{Filter, ImageTexture, Wrap};
+pub use noise::{ColorRamp, NoisePattern, NoiseTexture};
+pub use perlin::Perlin;
+
+// A color varying over a surface, looked up from the surface coordinates (u, v) of a hit point
+// or from the hit point itself for solid textures.
+pub trait Texture: Send + Sync {
+    fn value(&self, u: f32, v: f32, point: &Vec3) -> Vec3;
+}
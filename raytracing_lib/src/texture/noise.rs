@@ -0,0 +1,137 @@
+use super::{Perlin, Texture};
+use nalgebra_glm::Vec3;
+
+#[derive(Debug, Clone, Copy)]
+pub enum NoisePattern {
+    // smooth clouds
+    Fbm,
+    // cloudy pattern with sharp creases
+    Turbulence,
+    // veins along the z axis, distorted by turbulence
+    Marble,
+    // rings around the y axis, distorted by fbm
+    Wood,
+}
+
+// maps a value in [0, 1] to a color by linear interpolation between stops sorted by position
+#[derive(Debug, Clone)]
+pub struct ColorRamp {
+    stops: Vec<(f32, Vec3)>,
+}
+
+impl ColorRamp {
+    pub fn new(mut stops: Vec<(f32, Vec3)>) -> Result<Self, String> {
+        if stops.is_empty() {
+            return Err("A color ramp needs at least one stop".into());
+        }
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Ok(ColorRamp { stops })
+    }
+
+    pub fn grayscale() -> Self {
+        ColorRamp {
+            stops: vec![
+                (0.0, Vec3::new(0.0, 0.0, 0.0)),
+                (1.0, Vec3::new(1.0, 1.0, 1.0)),
+            ],
+        }
+    }
+
+    // white stone with dark grey veins
+    pub fn marble() -> Self {
+        ColorRamp {
+            stops: vec![
+                (0.0, Vec3::new(0.25, 0.25, 0.28)),
+                (0.3, Vec3::new(0.75, 0.75, 0.75)),
+                (1.0, Vec3::new(0.95, 0.95, 0.93)),
+            ],
+        }
+    }
+
+    // alternating light and dark brown rings
+    pub fn wood() -> Self {
+        ColorRamp {
+            stops: vec![
+                (0.0, Vec3::new(0.76, 0.55, 0.32)),
+                (0.6, Vec3::new(0.6, 0.4, 0.2)),
+                (1.0, Vec3::new(0.35, 0.2, 0.08)),
+            ],
+        }
+    }
+
+    pub fn color(&self, t: f32) -> Vec3 {
+        let index = self.stops.partition_point(|stop| stop.0 <= t);
+        if index == 0 {
+            return self.stops[0].1;
+        }
+        if index == self.stops.len() {
+            return self.stops[index - 1].1;
+        }
+        let (t0, c0) = self.stops[index - 1];
+        let (t1, c1) = self.stops[index];
+        let s = (t - t0) / (t1 - t0);
+        (1.0 - s) * c0 + s * c1
+    }
+}
+
+// a solid texture computed from Perlin noise, the pattern gives a value in [0, 1] turned into a
+// color by the ramp
+pub struct NoiseTexture {
+    perlin: Perlin,
+    pattern: NoisePattern,
+    scale: f32,
+    octaves: usize,
+    ramp: ColorRamp,
+}
+
+impl NoiseTexture {
+    pub fn new(pattern: NoisePattern, seed: u64) -> Self {
+        let ramp = match pattern {
+            NoisePattern::Marble => ColorRamp::marble(),
+            NoisePattern::Wood => ColorRamp::wood(),
+            _ => ColorRamp::grayscale(),
+        };
+        NoiseTexture {
+            perlin: Perlin::new(seed),
+            pattern,
+            scale: 1.0,
+            octaves: 7,
+            ramp,
+        }
+    }
+
+    // frequency of the pattern, the number of features per unit of length
+    pub fn scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn octaves(mut self, octaves: usize) -> Self {
+        self.octaves = octaves.max(1);
+        self
+    }
+
+    pub fn ramp(mut self, ramp: ColorRamp) -> Self {
+        self.ramp = ramp;
+        self
+    }
+}
+
+impl Texture for NoiseTexture {
+    #[allow(unused_variables)]
+    fn value(&self, u: f32, v: f32, point: &Vec3) -> Vec3 {
+        let p = point * self.scale;
+        let t = match self.pattern {
+            NoisePattern::Fbm => 0.5 * (1.0 + self.perlin.fbm(&p, self.octaves)),
+            NoisePattern::Turbulence => self.perlin.turbulence(&p, self.octaves),
+            NoisePattern::Marble => {
+                0.5 * (1.0 + f32::sin(p.z + 10.0 * self.perlin.turbulence(&p, self.octaves)))
+            }
+            NoisePattern::Wood => {
+                let distance = f32::sqrt(p.x * p.x + p.z * p.z);
+                (distance + 0.5 * self.perlin.fbm(&p, self.octaves)).rem_euclid(1.0)
+            }
+        };
+        self.ramp.color(t.clamp(0.0, 1.0))
+    }
+}
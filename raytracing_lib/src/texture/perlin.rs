@@ -0,0 +1,100 @@
+use nalgebra_glm::{dot, normalize, Vec3};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+const POINT_COUNT: usize = 256;
+
+// Gradient noise from Perlin, "Improving Noise" (SIGGRAPH 2002), with random gradients on a
+// lattice hashed by three permutation tables. The same seed always gives the same noise.
+pub struct Perlin {
+    gradients: Vec<Vec3>,
+    permutation_x: Vec<usize>,
+    permutation_y: Vec<usize>,
+    permutation_z: Vec<usize>,
+}
+
+impl Perlin {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let gradients = (0..POINT_COUNT)
+            .map(|_| loop {
+                let v = Vec3::new(
+                    rng.gen_range(-1.0, 1.0),
+                    rng.gen_range(-1.0, 1.0),
+                    rng.gen_range(-1.0, 1.0),
+                );
+                let norm2 = dot(&v, &v);
+                if norm2 > 1e-6 && norm2 <= 1.0 {
+                    break normalize(&v);
+                }
+            })
+            .collect();
+        let mut permutation = || {
+            let mut p: Vec<usize> = (0..POINT_COUNT).collect();
+            p.shuffle(&mut rng);
+            p
+        };
+        let permutation_x = permutation();
+        let permutation_y = permutation();
+        let permutation_z = permutation();
+        Perlin {
+            gradients,
+            permutation_x,
+            permutation_y,
+            permutation_z,
+        }
+    }
+
+    // smooth noise in about [-1, 1], zero on the points of the integer lattice
+    pub fn noise(&self, point: &Vec3) -> f32 {
+        let floor = point.map(f32::floor);
+        let fraction = point - floor;
+        let (i, j, k) = (floor.x as i64, floor.y as i64, floor.z as i64);
+        // hermite cubic smoothing of the interpolation weights
+        let smooth = fraction.map(|t| t * t * (3.0 - 2.0 * t));
+
+        let mut accumulator = 0.0;
+        for di in 0..2 {
+            for dj in 0..2 {
+                for dk in 0..2 {
+                    let hash = self.permutation_x[((i + di) & 255) as usize]
+                        ^ self.permutation_y[((j + dj) & 255) as usize]
+                        ^ self.permutation_z[((k + dk) & 255) as usize];
+                    let offset = fraction - Vec3::new(di as f32, dj as f32, dk as f32);
+                    let weight = |d: i64, s: f32| if d == 1 { s } else { 1.0 - s };
+                    accumulator += weight(di, smooth.x)
+                        * weight(dj, smooth.y)
+                        * weight(dk, smooth.z)
+                        * dot(&self.gradients[hash], &offset);
+                }
+            }
+        }
+        accumulator
+    }
+
+    // fractional Brownian motion: octaves of noise, each one twice the frequency and half the
+    // amplitude of the previous one, in about [-1, 1]
+    pub fn fbm(&self, point: &Vec3, octaves: usize) -> f32 {
+        let mut accumulator = 0.0;
+        let mut point = *point;
+        let mut weight = 1.0;
+        for _ in 0..octaves {
+            accumulator += weight * self.noise(&point);
+            weight *= 0.5;
+            point *= 2.0;
+        }
+        accumulator
+    }
+
+    // same as fbm with the absolute value of each octave, which creates sharp creases, in [0, 1]
+    pub fn turbulence(&self, point: &Vec3, octaves: usize) -> f32 {
+        let mut accumulator = 0.0;
+        let mut point = *point;
+        let mut weight = 1.0;
+        for _ in 0..octaves {
+            accumulator += weight * self.noise(&point).abs();
+            weight *= 0.5;
+            point *= 2.0;
+        }
+        accumulator.min(1.0)
+    }
+}
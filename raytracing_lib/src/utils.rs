@@ -1,4 +1,4 @@
-use nalgebra_glm::{length2, Vec3};
+use nalgebra_glm::{length2, normalize, Vec3};
 use rand::prelude::*;
 
 pub fn rand_range_f32(min: f32, max: f32) -> f32 {
@@ -7,7 +7,11 @@ pub fn rand_range_f32(min: f32, max: f32) -> f32 {
 
 pub fn random_in_unit_sphere() -> Vec3 {
     loop {
-        let p = Vec3::new_random();
+        let p = Vec3::new(
+            rand_range_f32(-1.0, 1.0),
+            rand_range_f32(-1.0, 1.0),
+            rand_range_f32(-1.0, 1.0),
+        );
         if length2(&p) < 1f32 {
             return p;
         };
@@ -35,3 +39,37 @@ pub fn schlick(cosine: f32, refraction_index: f32) -> f32 {
     let r0 = r0 * r0;
     r0 + (1.0 - r0) * f32::powi(1.0 - cosine, 5)
 }
+
+// returns two unit vectors forming an orthonormal basis with the unit vector n
+// (Duff et al., "Building an Orthonormal Basis, Revisited", JCGT 2017)
+pub fn orthonormal_basis(n: &Vec3) -> (Vec3, Vec3) {
+    let sign = f32::copysign(1.0, n.z);
+    let a = -1.0 / (sign + n.z);
+    let b = n.x * n.y * a;
+    (
+        Vec3::new(1.0 + sign * n.x * n.x * a, sign * b, -sign * n.x),
+        Vec3::new(b, sign + n.y * n.y * a, -n.y),
+    )
+}
+
+// returns a random direction inside the cone of directions from a point to a sphere of the given
+// radius at the given squared distance, around the unit axis w (uniform over the solid angle)
+pub fn random_to_sphere(w: &Vec3, radius: f32, distance_squared: f32) -> Vec3 {
+    let r1 = random::<f32>();
+    let r2 = random::<f32>();
+    let cos_theta_max = f32::sqrt(1.0 - radius * radius / distance_squared);
+    let z = 1.0 + r2 * (cos_theta_max - 1.0);
+    let phi = 2.0 * std::f32::consts::PI * r1;
+    let sin_theta = f32::sqrt(1.0 - z * z);
+    let (u, v) = orthonormal_basis(w);
+    normalize(&(u * f32::cos(phi) * sin_theta + v * f32::sin(phi) * sin_theta + w * z))
+}
+
+// inverse of the sRGB transfer function
+pub fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
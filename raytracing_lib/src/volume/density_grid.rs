@@ -0,0 +1,112 @@
+use super::DensityField;
+use nalgebra_glm::Vec3;
+use std::path::Path;
+
+// Dense grid of density values at the centers of its voxels, trilinearly interpolated in
+// between. Values are stored x first, then y, then z.
+pub struct DensityGrid {
+    size: [usize; 3],
+    data: Vec<f32>,
+    max_density: f32,
+}
+
+impl DensityGrid {
+    pub fn new(size: [usize; 3], data: Vec<f32>) -> Result<Self, String> {
+        let [nx, ny, nz] = size;
+        if nx == 0 || ny == 0 || nz == 0 || data.len() != nx * ny * nz {
+            return Err(format!(
+                "Density grid of {}x{}x{} voxels needs {} values, got {}",
+                nx,
+                ny,
+                nz,
+                nx * ny * nz,
+                data.len()
+            ));
+        }
+        if let Some(value) = data.iter().find(|d| !d.is_finite() || **d < 0.0) {
+            return Err(format!("Invalid density {} in grid", value));
+        }
+        let max_density = data.iter().cloned().fold(0.0, f32::max);
+        Ok(DensityGrid {
+            size,
+            data,
+            max_density,
+        })
+    }
+
+    // Loads a text file starting with the number of voxels along x, y and z, followed by the
+    // densities in the order of the grid, all separated by whitespace. Lines starting with #
+    // are comments.
+    pub fn from_file<P: AsRef<Path>>(path: &P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| format!("Cannot load density grid {}: {}", path.display(), err))?;
+        let mut tokens = content
+            .lines()
+            .filter(|line| !line.trim_start().starts_with('#'))
+            .flat_map(str::split_whitespace);
+        let in_file = |err: String| format!("Density grid {}: {}", path.display(), err);
+
+        let mut size = [0; 3];
+        for dimension in size.iter_mut() {
+            let token = tokens
+                .next()
+                .ok_or_else(|| in_file("missing grid size".into()))?;
+            *dimension = token
+                .parse()
+                .map_err(|_| in_file(format!("invalid grid size {}", token)))?;
+        }
+        let data = tokens
+            .map(|token| {
+                token
+                    .parse()
+                    .map_err(|_| in_file(format!("invalid density {}", token)))
+            })
+            .collect::<Result<Vec<f32>, String>>()?;
+        DensityGrid::new(size, data).map_err(in_file)
+    }
+
+    fn voxel(&self, x: usize, y: usize, z: usize) -> f32 {
+        let [nx, ny, _] = self.size;
+        self.data[(z * ny + y) * nx + x]
+    }
+}
+
+impl DensityField for DensityGrid {
+    fn density(&self, point: &Vec3) -> f32 {
+        if (0..3).any(|a| point[a] < 0.0 || point[a] > 1.0) {
+            return 0.0;
+        }
+        // position in voxels, relative to the center of the first one
+        let mut lower = [0; 3];
+        let mut upper = [0; 3];
+        let mut fraction = [0.0; 3];
+        for a in 0..3 {
+            let position = (point[a] * self.size[a] as f32 - 0.5).max(0.0);
+            lower[a] = (position as usize).min(self.size[a] - 1);
+            upper[a] = (lower[a] + 1).min(self.size[a] - 1);
+            fraction[a] = (position - lower[a] as f32).min(1.0);
+        }
+
+        let mut density = 0.0;
+        for corner in 0..8 {
+            let mut weight = 1.0;
+            let mut index = [0; 3];
+            for a in 0..3 {
+                if corner & (1 << a) == 0 {
+                    index[a] = lower[a];
+                    weight *= 1.0 - fraction[a];
+                } else {
+                    index[a] = upper[a];
+                    weight *= fraction[a];
+                }
+            }
+            density += weight * self.voxel(index[0], index[1], index[2]);
+        }
+        density
+    }
+
+    fn max_density(&self) -> f32 {
+        self.max_density
+    }
+}
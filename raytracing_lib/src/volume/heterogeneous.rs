@@ -0,0 +1,105 @@
+use super::{DensityField, Medium};
+use crate::{aabb::AABB, material::Material, ray::Ray};
+use nalgebra_glm::{length, Vec3};
+use std::sync::Arc;
+
+// A medium with a varying density filling an axis aligned box, like a cloud or a column of
+// smoke. The density field is stretched over the box. Both tracking methods sample tentative
+// collisions as if the medium had its maximum density everywhere, then delta tracking keeps a
+// collision with the probability of the actual density and ratio tracking weights the light
+// going through by it (Novák et al., "Residual Ratio Tracking", SIGGRAPH 2014).
+pub struct HeterogeneousMedium {
+    bounds: AABB,
+    density: Box<dyn DensityField>,
+    // converts the values of the density field to probabilities of collision per unit distance
+    density_scale: f32,
+
+    phase_function: Arc<Box<dyn Material>>,
+}
+
+impl HeterogeneousMedium {
+    // the corners can be given in any order
+    pub fn new(
+        a: Vec3,
+        b: Vec3,
+        density: impl DensityField + 'static,
+        phase_function: Arc<Box<dyn Material>>,
+    ) -> Self {
+        HeterogeneousMedium {
+            bounds: AABB {
+                min: a.inf(&b),
+                max: a.sup(&b),
+            },
+            density: Box::new(density),
+            density_scale: 1.0,
+            phase_function,
+        }
+    }
+
+    pub fn density_scale(mut self, density_scale: f32) -> Self {
+        self.density_scale = density_scale.max(0.0);
+        self
+    }
+
+    fn density_at(&self, point: &Vec3) -> f32 {
+        let local = (point - self.bounds.min).component_div(&(self.bounds.max - self.bounds.min));
+        self.density_scale * self.density.density(&local)
+    }
+
+    // the density the tentative collisions are sampled with, it must bound the actual one
+    fn majorant(&self) -> f32 {
+        self.density_scale * self.density.max_density()
+    }
+
+    // calls visit with the distances of tentative collisions between t_min and t_max, until it
+    // returns false, returns the distance of the last one visited
+    fn track(
+        &self,
+        r: &Ray,
+        t_min: f32,
+        t_max: f32,
+        mut visit: impl FnMut(f32) -> bool,
+    ) -> Option<f32> {
+        let (t_entry, t_exit) = self.bounds.intersect(r, t_min, t_max)?;
+        let majorant = self.majorant();
+        if majorant <= 0.0 {
+            return None;
+        }
+        let step = 1.0 / (majorant * length(&r.direction));
+        let mut t = t_entry;
+        loop {
+            t -= f32::ln(1.0 - rand::random::<f32>()) * step;
+            if t >= t_exit {
+                return None;
+            }
+            if !visit(t) {
+                return Some(t);
+            }
+        }
+    }
+}
+
+impl Medium for HeterogeneousMedium {
+    fn sample_collision(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<f32> {
+        let majorant = self.majorant();
+        // a tentative collision is a real one with probability density / majorant, the others
+        // are null collisions the ray goes on after
+        self.track(r, t_min, t_max, |t| {
+            rand::random::<f32>() * majorant >= self.density_at(&r.at(t))
+        })
+    }
+
+    fn transmittance(&self, r: &Ray, t_min: f32, t_max: f32) -> f32 {
+        let majorant = self.majorant();
+        let mut transmittance = 1.0;
+        self.track(r, t_min, t_max, |t| {
+            transmittance *= 1.0 - self.density_at(&r.at(t)) / majorant;
+            transmittance > 0.0
+        });
+        transmittance
+    }
+
+    fn phase_function(&self) -> &Arc<Box<dyn Material>> {
+        &self.phase_function
+    }
+}
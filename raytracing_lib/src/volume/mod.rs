@@ -0,0 +1,34 @@
+use crate::material::Material;
+use crate::ray::Ray;
+use nalgebra_glm::Vec3;
+use std::sync::Arc;
+
+mod density_grid;
+mod heterogeneous;
+mod noise_density;
+
+pub use density_grid::DensityGrid;
+pub use heterogeneous::HeterogeneousMedium;
+pub use noise_density::NoiseDensity;
+
+// A participating medium the renderer tracks rays through, next to the surfaces of the world.
+// Unlike ConstantMedium, which is a Hittable, the light going through a medium is estimated
+// without picking a collision, which makes shadow rays much less noisy.
+pub trait Medium: Send + Sync {
+    // distance along the ray of a collision with the medium in [t_min, t_max], None if the ray
+    // goes through. Collisions follow the transmittance of the medium (delta tracking).
+    fn sample_collision(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<f32>;
+    // unbiased estimate of the fraction of light going through the medium between t_min and
+    // t_max (ratio tracking)
+    fn transmittance(&self, r: &Ray, t_min: f32, t_max: f32) -> f32;
+    // material scattering the rays at collisions, like Isotropic or HenyeyGreenstein
+    fn phase_function(&self) -> &Arc<Box<dyn Material>>;
+}
+
+// Spatially varying density of a medium, over the unit cube [0, 1]³ that the medium stretches
+// over its bounds.
+pub trait DensityField: Send + Sync {
+    fn density(&self, point: &Vec3) -> f32;
+    // upper bound of the density anywhere in the unit cube
+    fn max_density(&self) -> f32;
+}
@@ -0,0 +1,50 @@
+use super::DensityField;
+use crate::texture::Perlin;
+use nalgebra_glm::Vec3;
+
+// Procedural cloud-like density made of Perlin fractal noise. The coverage is the fraction of
+// the noise values cut out as empty space, from 0 (dense fog everywhere) to 1 (nothing).
+pub struct NoiseDensity {
+    perlin: Perlin,
+    scale: f32,
+    octaves: usize,
+    coverage: f32,
+}
+
+impl NoiseDensity {
+    pub fn new(seed: u64) -> Self {
+        NoiseDensity {
+            perlin: Perlin::new(seed),
+            scale: 4.0,
+            octaves: 5,
+            coverage: 0.5,
+        }
+    }
+
+    // number of noise features across the volume
+    pub fn scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn octaves(mut self, octaves: usize) -> Self {
+        self.octaves = octaves.max(1);
+        self
+    }
+
+    pub fn coverage(mut self, coverage: f32) -> Self {
+        self.coverage = coverage.clamp(0.0, 0.99);
+        self
+    }
+}
+
+impl DensityField for NoiseDensity {
+    fn density(&self, point: &Vec3) -> f32 {
+        let noise = 0.5 * (1.0 + self.perlin.fbm(&(self.scale * point), self.octaves));
+        ((noise - self.coverage) / (1.0 - self.coverage)).clamp(0.0, 1.0)
+    }
+
+    fn max_density(&self) -> f32 {
+        1.0
+    }
+}
@@ -1,26 +1,98 @@
+use super::aabb::AABB;
 use super::bvh::BVHNode;
-use super::collision::Hittable;
+use super::collision::{HitRecord, Hittable, HittableList};
+use super::light::Light;
+use super::ray::Ray;
+use super::volume::Medium;
+use nalgebra_glm::normalize;
 use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct World {
-    bvh_tree: Arc<BVHNode>,
+    // None if the world only has unbounded objects
+    bvh_tree: Option<Arc<BVHNode>>,
+    // objects without a bounding box (like infinite planes) cannot go in the BVH
+    unbounded: Arc<HittableList>,
+    emitters: Arc<HittableList>,
+    lights: Arc<Vec<Box<dyn Light>>>,
+    media: Arc<Vec<Box<dyn Medium>>>,
 }
 
 pub struct WorldBuilder {
     hittables: Vec<Arc<dyn Hittable>>,
+    unbounded: HittableList,
+    emitters: HittableList,
+    lights: Vec<Box<dyn Light>>,
+    media: Vec<Box<dyn Medium>>,
+    // interval of the ray times, usually the shutter interval of the camera
+    time_interval: (f32, f32),
 }
 
 impl WorldBuilder {
+    fn push(&mut self, hittable: Arc<dyn Hittable>) {
+        let (time0, time1) = self.time_interval;
+        if hittable.bounding_box(time0, time1).is_some() {
+            self.hittables.push(hittable);
+        } else {
+            self.unbounded.add_hittable(hittable);
+        }
+    }
+
     pub fn add_object(&mut self, object: impl Hittable + 'static) -> &mut Self {
-        let hittable: Arc<dyn Hittable> = Arc::new(object) as Arc<dyn Hittable>;
-        self.hittables.push(hittable);
+        self.add_shared_object(Arc::new(object))
+    }
+
+    // adds an object that can also be used elsewhere, like in several transformed instances
+    pub fn add_shared_object(&mut self, object: Arc<dyn Hittable>) -> &mut Self {
+        self.push(object);
+        self
+    }
+
+    // adds an object with an emissive material, the renderer will cast shadow rays toward it
+    // if it can be sampled (see Hittable::random)
+    pub fn add_emitter(&mut self, object: impl Hittable + 'static) -> &mut Self {
+        self.add_shared_emitter(Arc::new(object))
+    }
+
+    pub fn add_shared_emitter(&mut self, object: Arc<dyn Hittable>) -> &mut Self {
+        self.push(Arc::clone(&object));
+        self.emitters.add_hittable(object);
+        self
+    }
+
+    pub fn add_light(&mut self, light: impl Light + 'static) -> &mut Self {
+        self.lights.push(Box::new(light));
+        self
+    }
+
+    // media are not objects, the renderer tracks rays through them between the surfaces
+    pub fn add_medium(&mut self, medium: impl Medium + 'static) -> &mut Self {
+        self.media.push(Box::new(medium));
+        self
+    }
+
+    // moving objects are bounded over this interval in the BVH, rays cast at other times can
+    // miss them
+    pub fn time_interval(&mut self, time0: f32, time1: f32) -> &mut Self {
+        self.time_interval = (time0, time1);
         self
     }
 
     pub fn build(self) -> World {
+        let (time0, time1) = self.time_interval;
+        let bvh_tree = if self.hittables.is_empty() {
+            None
+        } else {
+            Some(Arc::new(
+                BVHNode::new(&self.hittables[..], time0, time1).unwrap(),
+            ))
+        };
         World {
-            bvh_tree: Arc::new(BVHNode::new(&self.hittables[..]).unwrap()),
+            bvh_tree,
+            unbounded: Arc::new(self.unbounded),
+            emitters: Arc::new(self.emitters),
+            lights: Arc::new(self.lights),
+            media: Arc::new(self.media),
         }
     }
 }
@@ -29,10 +101,75 @@ impl World {
     pub fn builder() -> WorldBuilder {
         WorldBuilder {
             hittables: Vec::new(),
+            unbounded: HittableList::new(),
+            emitters: HittableList::new(),
+            lights: Vec::new(),
+            media: Vec::new(),
+            time_interval: (0.0, 1.0),
         }
     }
 
-    pub fn get_hittables(&self) -> Arc<BVHNode> {
-        Arc::clone(&self.bvh_tree)
+    // the BVH of the bounded objects of the world, None if there are none
+    pub fn get_hittables(&self) -> Option<Arc<BVHNode>> {
+        self.bvh_tree.as_ref().map(Arc::clone)
+    }
+
+    pub fn get_emitters(&self) -> Arc<HittableList> {
+        Arc::clone(&self.emitters)
+    }
+
+    pub fn get_lights(&self) -> Arc<Vec<Box<dyn Light>>> {
+        Arc::clone(&self.lights)
+    }
+
+    // closest collision of the ray with the media before t_max, their phase function is the
+    // material of the hit
+    pub fn media_hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        let (t, medium) = self
+            .media
+            .iter()
+            .filter_map(|medium| {
+                medium
+                    .sample_collision(r, t_min, t_max)
+                    .map(|t| (t, medium))
+            })
+            .min_by(|(t0, _), (t1, _)| t0.total_cmp(t1))?;
+        // there is no surface, the normal only needs to face the ray
+        Some(HitRecord::new(
+            r,
+            t,
+            &-normalize(&r.direction),
+            0.0,
+            0.0,
+            Arc::clone(medium.phase_function()),
+        ))
+    }
+
+    // fraction of the light going through the media between t_min and t_max
+    pub fn transmittance(&self, r: &Ray, t_min: f32, t_max: f32) -> f32 {
+        self.media
+            .iter()
+            .map(|medium| medium.transmittance(r, t_min, t_max))
+            .product()
+    }
+}
+
+impl Hittable for World {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        let bvh_hit = self
+            .bvh_tree
+            .as_ref()
+            .and_then(|bvh_tree| bvh_tree.hit(r, t_min, t_max));
+        let closest_so_far = bvh_hit.as_ref().map_or(t_max, |record| record.t);
+        self.unbounded.hit(r, t_min, closest_so_far).or(bvh_hit)
+    }
+
+    fn bounding_box(&self, t0: f32, t1: f32) -> Option<AABB> {
+        if !self.unbounded.is_empty() {
+            return None;
+        }
+        self.bvh_tree
+            .as_ref()
+            .and_then(|bvh_tree| bvh_tree.bounding_box(t0, t1))
     }
 }